@@ -0,0 +1,62 @@
+// librocksdb-sys links a preinstalled librocksdb instead of building it when
+// ROCKSDB_LIB_DIR is set. The bindings are for RocksDB 6.20, check that the
+// installed headers are compatible before linking against them.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+const MIN_VERSION: (u32, u32) = (6, 20);
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=ROCKSDB_LIB_DIR");
+    println!("cargo:rerun-if-env-changed=ROCKSDB_INCLUDE_DIR");
+
+    let lib_dir = match env::var_os("ROCKSDB_LIB_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => return,
+    };
+    let include_dir = match env::var_os("ROCKSDB_INCLUDE_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => lib_dir.join("../include"),
+    };
+    let header = include_dir.join("rocksdb/version.h");
+    println!("cargo:rerun-if-changed={}", header.display());
+
+    let content = match fs::read_to_string(&header) {
+        Ok(content) => content,
+        Err(e) => {
+            println!(
+                "cargo:warning=cannot read {}: {}, skipping the RocksDB version check \
+                 (set ROCKSDB_INCLUDE_DIR to enable it)",
+                header.display(),
+                e
+            );
+            return;
+        }
+    };
+    let major = define(&content, "ROCKSDB_MAJOR");
+    let minor = define(&content, "ROCKSDB_MINOR");
+    match (major, minor) {
+        (Some(major), Some(minor)) if major == MIN_VERSION.0 && minor >= MIN_VERSION.1 => {}
+        (Some(major), Some(minor)) => panic!(
+            "system RocksDB {}.{} in {} is not supported, need {}.x with x >= {}",
+            major,
+            minor,
+            lib_dir.display(),
+            MIN_VERSION.0,
+            MIN_VERSION.1
+        ),
+        _ => panic!("cannot find the RocksDB version in {}", header.display()),
+    }
+}
+
+fn define(content: &str, name: &str) -> Option<u32> {
+    content.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some("#define"), Some(n), Some(value)) if n == name => value.parse().ok(),
+            _ => None,
+        }
+    })
+}
@@ -0,0 +1,113 @@
+// Dump and load a db as a stream of records, each being a big-endian u32
+// key length, the key, a big-endian u32 value length and the value.
+
+use std::io::{ErrorKind, Read, Write};
+
+use crate::{BulkWriter, Error, KeyRange, ReadOptions, Result, DB};
+
+// returns the number of records written
+pub fn dump(db: &DB, mut writer: impl Write, range: impl KeyRange) -> Result<u64> {
+    let mut options = ReadOptions::new();
+    options.set_fill_cache(false);
+    let mut count = 0;
+    for item in db.range(options, range) {
+        let (key, value) = item?;
+        write_field(&mut writer, &key)?;
+        write_field(&mut writer, &value)?;
+        count += 1;
+    }
+    writer.flush()?;
+    Ok(count)
+}
+
+// Returns the number of records loaded. Records are written in batches as
+// they are read, so a load that fails part way leaves the records before the
+// failing one in the db.
+pub fn load(db: &DB, mut reader: impl Read) -> Result<u64> {
+    let mut writer = BulkWriter::new(db);
+    let mut count = 0;
+    while let Some(key) = read_field(&mut reader, true)? {
+        let value = read_field(&mut reader, false)?.unwrap();
+        writer.put(key, value)?;
+        count += 1;
+    }
+    writer.finish()?;
+    Ok(count)
+}
+
+fn write_field(writer: &mut impl Write, field: &[u8]) -> Result<()> {
+    let len = u32::try_from(field.len())
+        .map_err(|_| Error::invalid_argument("record is larger than 4GiB"))?;
+    writer.write_all(&len.to_be_bytes())?;
+    writer.write_all(field)?;
+    Ok(())
+}
+
+// a clean end of input is only allowed before a key
+fn read_field(reader: &mut impl Read, eof_ok: bool) -> Result<Option<Vec<u8>>> {
+    let mut len = [0; 4];
+    let mut read = 0;
+    while read < len.len() {
+        match reader.read(&mut len[read..]) {
+            Ok(0) if read == 0 && eof_ok => return Ok(None),
+            Ok(0) => return Err(Error::corruption("truncated dump")),
+            Ok(n) => read += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    // the buffer grows with the input instead of trusting a corrupt length
+    let len = u32::from_be_bytes(len) as u64;
+    let mut field = Vec::new();
+    reader.take(len).read_to_end(&mut field)?;
+    if field.len() as u64 != len {
+        return Err(Error::corruption("truncated dump"));
+    }
+    Ok(Some(field))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::options::tests::DBPath;
+    use crate::{admin, Options, DB};
+
+    #[test]
+    fn test_dump_load() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        for i in 0..10u8 {
+            db.put_default([i], vec![i; i as usize]).unwrap();
+        }
+
+        let mut dump = Vec::new();
+        assert_eq!(admin::dump(&db, &mut dump, [2u8]..[8u8]).unwrap(), 6);
+
+        let path = DBPath::new();
+        let copy = DB::open(&options, path.as_ref()).unwrap();
+        assert_eq!(admin::load(&copy, &dump[..]).unwrap(), 6);
+        assert!(copy.get_default([1u8]).unwrap().is_none());
+        for i in 2..8u8 {
+            assert_eq!(
+                copy.get_default([i]).unwrap().unwrap().to_vec(),
+                vec![i; i as usize]
+            );
+        }
+
+        assert!(admin::load(&copy, &dump[..dump.len() - 1]).is_err());
+
+        let path = DBPath::new();
+        let partial = DB::open(&options, path.as_ref()).unwrap();
+        assert!(admin::load(&partial, &dump[..dump.len() - 1]).is_err());
+        for i in 2..7u8 {
+            assert!(partial.get_default([i]).unwrap().is_some());
+        }
+        assert!(partial.get_default([7u8]).unwrap().is_none());
+
+        // a corrupt length doesn't allocate its size up front
+        let mut dump = vec![0, 0, 0, 1, b'k'];
+        dump.extend_from_slice(&u32::MAX.to_be_bytes());
+        assert!(admin::load(&copy, &dump[..]).is_err());
+    }
+}
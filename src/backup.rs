@@ -0,0 +1,190 @@
+use std::path::Path;
+
+use librocksdb_sys::*;
+
+use crate::{path_to_cstring, Options, Result, DB};
+
+// Incremental backups of a db into a backup directory. Table files shared
+// between backups are only copied once.
+pub struct BackupEngine {
+    inner: *mut rocksdb_backup_engine_t,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupInfo {
+    pub id: u32,
+    // unix seconds
+    pub timestamp: i64,
+    pub size: u64,
+    pub num_files: u32,
+}
+
+impl BackupEngine {
+    pub fn open(options: &Options, backup_dir: impl AsRef<Path>) -> Result<Self> {
+        let path = path_to_cstring(backup_dir.as_ref())?;
+        Ok(Self {
+            inner: ffi!(rocksdb_backup_engine_open(options.inner, path.as_ptr())),
+        })
+    }
+
+    // flushes the memtables first, so the backup needs no WAL files
+    pub fn create_new_backup(&mut self, db: &DB) -> Result<()> {
+        self.create_new_backup_flush(db, true)
+    }
+
+    pub fn create_new_backup_flush(&mut self, db: &DB, flush_before_backup: bool) -> Result<()> {
+        Ok(ffi!(rocksdb_backup_engine_create_new_backup_flush(
+            self.inner,
+            db.inner,
+            flush_before_backup as _
+        )))
+    }
+
+    // oldest first
+    pub fn get_backup_info(&self) -> Vec<BackupInfo> {
+        unsafe {
+            let info = rocksdb_backup_engine_get_backup_info(self.inner);
+            let backups = (0..rocksdb_backup_engine_info_count(info))
+                .map(|i| BackupInfo {
+                    id: rocksdb_backup_engine_info_backup_id(info, i),
+                    timestamp: rocksdb_backup_engine_info_timestamp(info, i),
+                    size: rocksdb_backup_engine_info_size(info, i),
+                    num_files: rocksdb_backup_engine_info_number_files(info, i),
+                })
+                .collect();
+            rocksdb_backup_engine_info_destroy(info);
+            backups
+        }
+    }
+
+    // deletes all but the newest num_backups_to_keep backups
+    pub fn purge_old_backups(&mut self, num_backups_to_keep: u32) -> Result<()> {
+        Ok(ffi!(rocksdb_backup_engine_purge_old_backups(
+            self.inner,
+            num_backups_to_keep
+        )))
+    }
+
+    // checks that the files of the backup exist and have the right sizes
+    pub fn verify_backup(&self, backup_id: u32) -> Result<()> {
+        Ok(ffi!(rocksdb_backup_engine_verify_backup(
+            self.inner, backup_id
+        )))
+    }
+
+    // The db must not be open. Existing files in db_dir and wal_dir are
+    // replaced.
+    pub fn restore_from_latest_backup(
+        &mut self,
+        db_dir: impl AsRef<Path>,
+        wal_dir: impl AsRef<Path>,
+        options: &RestoreOptions,
+    ) -> Result<()> {
+        let db_dir = path_to_cstring(db_dir.as_ref())?;
+        let wal_dir = path_to_cstring(wal_dir.as_ref())?;
+        Ok(ffi!(rocksdb_backup_engine_restore_db_from_latest_backup(
+            self.inner,
+            db_dir.as_ptr(),
+            wal_dir.as_ptr(),
+            options.inner
+        )))
+    }
+
+    pub fn restore_from_backup(
+        &mut self,
+        db_dir: impl AsRef<Path>,
+        wal_dir: impl AsRef<Path>,
+        options: &RestoreOptions,
+        backup_id: u32,
+    ) -> Result<()> {
+        let db_dir = path_to_cstring(db_dir.as_ref())?;
+        let wal_dir = path_to_cstring(wal_dir.as_ref())?;
+        Ok(ffi!(rocksdb_backup_engine_restore_db_from_backup(
+            self.inner,
+            db_dir.as_ptr(),
+            wal_dir.as_ptr(),
+            options.inner,
+            backup_id
+        )))
+    }
+}
+
+impl Drop for BackupEngine {
+    fn drop(&mut self) {
+        unsafe { rocksdb_backup_engine_close(self.inner) }
+    }
+}
+
+unsafe impl Send for BackupEngine {}
+
+unsafe impl Sync for BackupEngine {}
+
+define!(
+    RestoreOptions,
+    rocksdb_restore_options_t,
+    rocksdb_restore_options_create,
+    rocksdb_restore_options_destroy
+);
+
+impl RestoreOptions {
+    // keeps the WAL files in wal_dir instead of replacing them with the ones
+    // in the backup
+    pub fn set_keep_log_files(&mut self, keep: bool) -> &mut Self {
+        unsafe { rocksdb_restore_options_set_keep_log_files(self.inner, keep as _) };
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::options::tests::DBPath;
+    use crate::{BackupEngine, Options, RestoreOptions, DB};
+
+    #[test]
+    fn test_backup_and_restore() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let (path, backup_path, restore_path) = (DBPath::new(), DBPath::new(), DBPath::new());
+        let mut engine = BackupEngine::open(&options, backup_path.as_ref()).unwrap();
+        {
+            let db = DB::open(&options, path.as_ref()).unwrap();
+            db.put_default("a", "1").unwrap();
+            engine.create_new_backup(&db).unwrap();
+            db.put_default("b", "2").unwrap();
+            engine.create_new_backup(&db).unwrap();
+        }
+
+        let backups = engine.get_backup_info();
+        assert_eq!(backups.len(), 2);
+        assert!(backups[0].id < backups[1].id);
+        assert!(backups[1].num_files > 0);
+        engine.verify_backup(backups[1].id).unwrap();
+
+        engine.purge_old_backups(1).unwrap();
+        assert_eq!(engine.get_backup_info(), backups[1..]);
+        assert!(engine.verify_backup(backups[0].id).is_err());
+
+        let restore_options = RestoreOptions::new();
+        engine
+            .restore_from_latest_backup(
+                restore_path.as_ref(),
+                restore_path.as_ref(),
+                &restore_options,
+            )
+            .unwrap();
+        let db = DB::open(&options, restore_path.as_ref()).unwrap();
+        assert_eq!(db.get_default("a").unwrap().unwrap(), b"1");
+        assert_eq!(db.get_default("b").unwrap().unwrap(), b"2");
+        drop(db);
+
+        let path = DBPath::new();
+        engine
+            .restore_from_backup(
+                path.as_ref(),
+                path.as_ref(),
+                &restore_options,
+                backups[1].id,
+            )
+            .unwrap();
+    }
+}
@@ -0,0 +1,301 @@
+// A small db_bench: runs one workload against a db at a given path and
+// reports throughput and latency percentiles, to compare option changes
+// reproducibly. Read workloads load the keys first, outside the measurement.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::instrumented_db::Recorder;
+use crate::rng::Rng;
+use crate::{Error, OpStats, Options, Result, WriteOptions, DB};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Workload {
+    FillSeq,
+    FillRandom,
+    ReadRandom,
+    // threads read while one more thread keeps writing random keys
+    ReadWhileWriting,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    Default,
+    BulkLoad,
+    // with a 64MB block cache
+    PointLookup,
+}
+
+#[derive(Debug, Clone)]
+pub struct BenchConfig {
+    pub workload: Workload,
+    // number of keys, also the total number of operations
+    pub num: u64,
+    pub key_size: usize,
+    pub value_size: usize,
+    pub threads: usize,
+    pub preset: Preset,
+    pub seed: u64,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            workload: Workload::FillRandom,
+            num: 100_000,
+            key_size: 16,
+            value_size: 100,
+            threads: 1,
+            preset: Preset::Default,
+            seed: 301,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    pub ops: u64,
+    // key and value bytes written or read
+    pub bytes: u64,
+    // reads that found their key
+    pub found: u64,
+    pub elapsed: Duration,
+    pub latency: OpStats,
+}
+
+impl BenchReport {
+    pub fn ops_per_sec(&self) -> f64 {
+        self.ops as f64 / self.elapsed.as_secs_f64()
+    }
+
+    pub fn mb_per_sec(&self) -> f64 {
+        self.bytes as f64 / (1 << 20) as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+#[derive(Default)]
+struct Counters {
+    ops: AtomicU64,
+    bytes: AtomicU64,
+    found: AtomicU64,
+}
+
+pub fn run(config: &BenchConfig, path: impl AsRef<Path>) -> Result<BenchReport> {
+    if config.num == 0 || config.threads == 0 {
+        return Err(Error::invalid_argument(
+            "bench needs at least one operation and one thread",
+        ));
+    }
+    let db = DB::open(&options(config.preset), path)?;
+    if let Workload::ReadRandom | Workload::ReadWhileWriting = config.workload {
+        fill(
+            &db,
+            config,
+            0,
+            0..config.num,
+            false,
+            &Counters::default(),
+            None,
+        )?;
+    }
+
+    let counters = Counters::default();
+    let recorder = Recorder::default();
+    let writing = AtomicBool::new(true);
+    let per_thread = config.num / config.threads as u64;
+    let start = Instant::now();
+    thread::scope(|scope| {
+        let (db, counters, recorder) = (&db, &counters, &recorder);
+        let mut handles = Vec::new();
+        for i in 0..config.threads {
+            let seed = config.seed.wrapping_add(i as u64);
+            // the last thread also does the remainder
+            let end = if i + 1 == config.threads {
+                config.num
+            } else {
+                (i as u64 + 1) * per_thread
+            };
+            let range = i as u64 * per_thread..end;
+            handles.push(scope.spawn(move || match config.workload {
+                Workload::FillSeq => fill(db, config, seed, range, false, counters, Some(recorder)),
+                Workload::FillRandom => {
+                    fill(db, config, seed, range, true, counters, Some(recorder))
+                }
+                Workload::ReadRandom | Workload::ReadWhileWriting => read(
+                    db,
+                    config,
+                    seed,
+                    range.end - range.start,
+                    counters,
+                    recorder,
+                ),
+            }));
+        }
+        let writer = (config.workload == Workload::ReadWhileWriting).then(|| {
+            let (config, writing) = (config, &writing);
+            scope.spawn(move || {
+                let mut rng = Rng::new(config.seed.wrapping_sub(1));
+                let value = value(&mut rng, config.value_size);
+                while writing.load(Ordering::Relaxed) {
+                    db.put(
+                        &WriteOptions::new(),
+                        key(rng.below(config.num), config.key_size),
+                        &value,
+                    )?;
+                }
+                Ok(())
+            })
+        });
+        let result = handles.into_iter().try_for_each(join);
+        writing.store(false, Ordering::Relaxed);
+        result.and(writer.map_or(Ok(()), join))
+    })?;
+
+    Ok(BenchReport {
+        ops: counters.ops.into_inner(),
+        bytes: counters.bytes.into_inner(),
+        found: counters.found.into_inner(),
+        elapsed: start.elapsed(),
+        latency: recorder.snapshot(),
+    })
+}
+
+fn join(handle: thread::ScopedJoinHandle<'_, Result<()>>) -> Result<()> {
+    handle
+        .join()
+        .unwrap_or_else(|_| Err(Error::corruption("bench thread panicked")))
+}
+
+fn options(preset: Preset) -> Options {
+    let mut options = Options::new();
+    options.set_create_if_missing(true);
+    match preset {
+        Preset::Default => {}
+        Preset::BulkLoad => {
+            options.prepare_for_bulk_load();
+        }
+        Preset::PointLookup => {
+            options.optimize_for_point_lookup(64);
+        }
+    }
+    options
+}
+
+// the number in big-endian, zero padded on the left to `size` bytes
+fn key(n: u64, size: usize) -> Vec<u8> {
+    let mut key = vec![0; size.max(8)];
+    let len = key.len();
+    key[len - 8..].copy_from_slice(&n.to_be_bytes());
+    key.split_off(len - size.max(1).min(len))
+}
+
+fn value(rng: &mut Rng, size: usize) -> Vec<u8> {
+    (0..size).map(|_| rng.next() as u8).collect()
+}
+
+fn fill(
+    db: &DB,
+    config: &BenchConfig,
+    seed: u64,
+    range: std::ops::Range<u64>,
+    random: bool,
+    counters: &Counters,
+    recorder: Option<&Recorder>,
+) -> Result<()> {
+    let mut rng = Rng::new(seed);
+    let options = WriteOptions::new();
+    let value = value(&mut rng, config.value_size);
+    for n in range {
+        let n = if random { rng.below(config.num) } else { n };
+        let key = key(n, config.key_size);
+        let start = Instant::now();
+        let result = db.put(&options, &key, &value);
+        if let Some(recorder) = recorder {
+            recorder.record(start.elapsed(), result.is_err());
+        }
+        result?;
+        counters.ops.fetch_add(1, Ordering::Relaxed);
+        counters
+            .bytes
+            .fetch_add((key.len() + value.len()) as u64, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+fn read(
+    db: &DB,
+    config: &BenchConfig,
+    seed: u64,
+    ops: u64,
+    counters: &Counters,
+    recorder: &Recorder,
+) -> Result<()> {
+    let mut rng = Rng::new(seed);
+    for _ in 0..ops {
+        let key = key(rng.below(config.num), config.key_size);
+        let start = Instant::now();
+        let result = db.get_pinned(&Default::default(), &key);
+        recorder.record(start.elapsed(), result.is_err());
+        if let Some(value) = result? {
+            counters.found.fetch_add(1, Ordering::Relaxed);
+            counters
+                .bytes
+                .fetch_add((key.len() + value.as_ref().len()) as u64, Ordering::Relaxed);
+        }
+        counters.ops.fetch_add(1, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bench::{key, run, BenchConfig, Preset, Workload};
+    use crate::options::tests::DBPath;
+    use crate::ErrorKind;
+
+    #[test]
+    fn test_key() {
+        assert_eq!(key(1, 4), [0, 0, 0, 1]);
+        assert_eq!(key(0x0102, 10), [0, 0, 0, 0, 0, 0, 0, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_bench() {
+        for (workload, preset) in [
+            (Workload::FillSeq, Preset::BulkLoad),
+            (Workload::FillRandom, Preset::Default),
+            (Workload::ReadRandom, Preset::PointLookup),
+            (Workload::ReadWhileWriting, Preset::Default),
+        ] {
+            let config = BenchConfig {
+                workload,
+                preset,
+                num: 1000,
+                threads: 3,
+                ..Default::default()
+            };
+            let path = DBPath::new();
+            let report = run(&config, path.as_ref()).unwrap();
+            assert_eq!(report.ops, 1000);
+            assert_eq!(report.latency.count, 1000);
+            if workload == Workload::ReadRandom {
+                assert_eq!(report.found, 1000);
+            }
+        }
+
+        for (num, threads) in [(0, 1), (1, 0)] {
+            let config = BenchConfig {
+                num,
+                threads,
+                ..Default::default()
+            };
+            let path = DBPath::new();
+            assert_eq!(
+                run(&config, path.as_ref()).unwrap_err().kind(),
+                ErrorKind::InvalidArgument
+            );
+        }
+    }
+}
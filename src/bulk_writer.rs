@@ -0,0 +1,142 @@
+use crate::{FlushOptions, Result, WriteBatch, WriteOptions, DB};
+
+// Buffers puts and deletes and writes them in batches of bounded size, so
+// loading a large data set doesn't build one huge WriteBatch in memory.
+// Like BufWriter, pending operations are written on drop and errors there
+// are ignored; call finish to see them.
+pub struct BulkWriter<'a> {
+    db: &'a DB,
+    options: WriteOptions,
+    batch: WriteBatch,
+    max_batch_count: usize,
+    max_batch_bytes: usize,
+    compact_on_finish: bool,
+}
+
+impl<'a> BulkWriter<'a> {
+    pub fn new(db: &'a DB) -> Self {
+        Self {
+            db,
+            options: WriteOptions::new(),
+            batch: WriteBatch::new(),
+            max_batch_count: 10_000,
+            max_batch_bytes: 4 << 20,
+            compact_on_finish: false,
+        }
+    }
+
+    pub fn set_max_batch_count(&mut self, count: usize) -> &mut Self {
+        self.max_batch_count = count;
+        self
+    }
+
+    pub fn set_max_batch_bytes(&mut self, bytes: usize) -> &mut Self {
+        self.max_batch_bytes = bytes;
+        self
+    }
+
+    // without the WAL, finish flushes the memtables to make the data durable
+    pub fn set_disable_wal(&mut self, disable: bool) -> &mut Self {
+        self.options.set_disable_wal(disable);
+        self
+    }
+
+    pub fn set_compact_on_finish(&mut self, compact: bool) -> &mut Self {
+        self.compact_on_finish = compact;
+        self
+    }
+
+    pub fn put(&mut self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) -> Result<()> {
+        self.batch.put(key, value);
+        self.maybe_write()
+    }
+
+    pub fn delete(&mut self, key: impl AsRef<[u8]>) -> Result<()> {
+        self.batch.delete(key);
+        self.maybe_write()
+    }
+
+    fn maybe_write(&mut self) -> Result<()> {
+        if self.batch.count() as usize >= self.max_batch_count
+            || self.batch.size_in_bytes() >= self.max_batch_bytes
+        {
+            self.write()?;
+        }
+        Ok(())
+    }
+
+    // writes the pending batch
+    pub fn write(&mut self) -> Result<()> {
+        if self.batch.count() > 0 {
+            self.db.write(&self.options, &self.batch)?;
+            self.batch.clear();
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<()> {
+        self.write()?;
+        if self.options.get_disable_wal() {
+            let mut options = FlushOptions::new();
+            options.set_wait(true);
+            self.db.flush(&options)?;
+        }
+        if self.compact_on_finish {
+            self.db.compact_range(None, None);
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Drop for BulkWriter<'a> {
+    fn drop(&mut self) {
+        let _ = self.write();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::options::tests::DBPath;
+    use crate::{BulkWriter, Options, DB};
+
+    #[test]
+    fn test_bulk_writer() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+
+        let mut writer = BulkWriter::new(&db);
+        writer
+            .set_max_batch_count(100)
+            .set_disable_wal(true)
+            .set_compact_on_finish(true);
+        for i in 0..1000u32 {
+            writer.put(i.to_be_bytes(), i.to_le_bytes()).unwrap();
+        }
+        // the last full batch has been written, nothing is pending
+        assert_eq!(
+            db.get_default(999u32.to_be_bytes()).unwrap().unwrap(),
+            999u32.to_le_bytes()
+        );
+        writer.delete(0u32.to_be_bytes()).unwrap();
+        assert!(db.get_default(0u32.to_be_bytes()).unwrap().is_some());
+        writer.finish().unwrap();
+
+        assert!(db.get_default(0u32.to_be_bytes()).unwrap().is_none());
+        assert_eq!(db.iter(&Default::default()).count(), 999);
+    }
+
+    #[test]
+    fn test_bulk_writer_drop() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+
+        let mut writer = BulkWriter::new(&db);
+        writer.put("foo", "bar").unwrap();
+        drop(writer);
+        assert_eq!(db.get_default("foo").unwrap().unwrap(), b"bar");
+    }
+}
@@ -1,9 +1,15 @@
 use std::ascii::escape_default;
+use std::borrow::Borrow;
+use std::cmp::Ordering;
 use std::fmt::{Debug, Formatter, Write};
+use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
+use std::ops::Deref;
 use std::os::raw::c_char;
 use std::slice::{from_raw_parts, from_raw_parts_mut};
 
+use librocksdb_sys::*;
+
 use crate::free;
 
 pub struct Bytes {
@@ -18,6 +24,29 @@ impl Bytes {
     }
 }
 
+impl Bytes {
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.as_ref().to_vec()
+    }
+
+    pub fn into_vec(self) -> Vec<u8> {
+        self.to_vec()
+    }
+}
+
+impl From<Bytes> for Vec<u8> {
+    fn from(bytes: Bytes) -> Self {
+        bytes.into_vec()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Bytes {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.as_ref())
+    }
+}
+
 impl AsRef<[u8]> for Bytes {
     fn as_ref(&self) -> &[u8] {
         unsafe { from_raw_parts(self.ptr as _, self.len) }
@@ -36,6 +65,82 @@ impl Debug for Bytes {
     }
 }
 
+impl Deref for Bytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_ref()
+    }
+}
+
+impl Borrow<[u8]> for Bytes {
+    fn borrow(&self) -> &[u8] {
+        self.as_ref()
+    }
+}
+
+impl PartialEq for Bytes {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+impl Eq for Bytes {}
+
+impl PartialEq<[u8]> for Bytes {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.as_ref() == other
+    }
+}
+
+impl PartialEq<&[u8]> for Bytes {
+    fn eq(&self, other: &&[u8]) -> bool {
+        self.as_ref() == *other
+    }
+}
+
+impl<const N: usize> PartialEq<[u8; N]> for Bytes {
+    fn eq(&self, other: &[u8; N]) -> bool {
+        self.as_ref() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&[u8; N]> for Bytes {
+    fn eq(&self, other: &&[u8; N]) -> bool {
+        self.as_ref() == *other
+    }
+}
+
+impl PartialEq<str> for Bytes {
+    fn eq(&self, other: &str) -> bool {
+        self.as_ref() == other.as_bytes()
+    }
+}
+
+impl PartialEq<&str> for Bytes {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_ref() == other.as_bytes()
+    }
+}
+
+impl PartialOrd for Bytes {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Bytes {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_ref().cmp(other.as_ref())
+    }
+}
+
+impl Hash for Bytes {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_ref().hash(state)
+    }
+}
+
 impl Drop for Bytes {
     fn drop(&mut self) {
         free(self.ptr)
@@ -68,6 +173,43 @@ impl<'a> Debug for Slice<'a> {
     }
 }
 
+pub struct PinnableSlice<'a> {
+    inner: *mut rocksdb_pinnableslice_t,
+    _marker: PhantomData<&'a ()>,
+}
+
+impl<'a> PinnableSlice<'a> {
+    pub(crate) fn new(inner: *mut rocksdb_pinnableslice_t) -> Self {
+        debug_assert!(!inner.is_null());
+        let _marker = PhantomData;
+        Self { inner, _marker }
+    }
+}
+
+impl<'a> AsRef<[u8]> for PinnableSlice<'a> {
+    fn as_ref(&self) -> &[u8] {
+        let mut len: usize = 0;
+        let ptr = unsafe { rocksdb_pinnableslice_value(self.inner, &mut len) };
+        unsafe { from_raw_parts(ptr as _, len) }
+    }
+}
+
+impl<'a> Debug for PinnableSlice<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        format_slice(self.as_ref(), f)
+    }
+}
+
+impl<'a> Drop for PinnableSlice<'a> {
+    fn drop(&mut self) {
+        unsafe { rocksdb_pinnableslice_destroy(self.inner) }
+    }
+}
+
+unsafe impl<'a> Send for PinnableSlice<'a> {}
+
+unsafe impl<'a> Sync for PinnableSlice<'a> {}
+
 fn format_slice(s: &[u8], f: &mut Formatter<'_>) -> std::fmt::Result {
     write!(f, "\"")?;
     for byte in s.iter().flat_map(|&b| escape_default(b)) {
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use librocksdb_sys::*;
+
+// A block or row cache. Clones share the same cache, and one cache can be set
+// on the options of any number of databases to give them a single memory
+// budget. The databases keep their own reference, the Cache can be dropped
+// once the options are set.
+#[derive(Clone)]
+pub struct Cache {
+    inner: Arc<RawCache>,
+}
+
+struct RawCache(*mut rocksdb_cache_t);
+
+impl Cache {
+    pub fn new_lru(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(RawCache(unsafe { rocksdb_cache_create_lru(capacity) })),
+        }
+    }
+
+    pub(crate) fn as_ptr(&self) -> *mut rocksdb_cache_t {
+        self.inner.0
+    }
+
+    // applies to every database using the cache
+    pub fn set_capacity(&self, capacity: usize) {
+        unsafe { rocksdb_cache_set_capacity(self.as_ptr(), capacity) }
+    }
+
+    pub fn get_capacity(&self) -> usize {
+        unsafe { rocksdb_cache_get_capacity(self.as_ptr()) }
+    }
+
+    pub fn get_usage(&self) -> usize {
+        unsafe { rocksdb_cache_get_usage(self.as_ptr()) }
+    }
+
+    pub fn get_pinned_usage(&self) -> usize {
+        unsafe { rocksdb_cache_get_pinned_usage(self.as_ptr()) }
+    }
+}
+
+impl Drop for RawCache {
+    fn drop(&mut self) {
+        unsafe { rocksdb_cache_destroy(self.0) }
+    }
+}
+
+unsafe impl Send for RawCache {}
+
+unsafe impl Sync for RawCache {}
+
+#[cfg(test)]
+mod tests {
+    use crate::options::tests::DBPath;
+    use crate::{
+        BlockBasedTableOptions, Cache, FlushOptions, Options, TransactionDB, TransactionDBOptions,
+        DB,
+    };
+
+    #[test]
+    fn test_shared_cache() {
+        let cache = Cache::new_lru(1 << 20);
+        let mut table_options = BlockBasedTableOptions::new();
+        table_options.set_block_cache(&cache);
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_block_based_table_factory(&table_options);
+        drop(table_options);
+
+        // TransactionDB can't flush, put a table file in place beforehand
+        let path2 = DBPath::new();
+        let db = DB::open(&options, path2.as_ref()).unwrap();
+        db.put_default("key", "value").unwrap();
+        db.flush(&FlushOptions::new()).unwrap();
+        drop(db);
+
+        let path1 = DBPath::new();
+        let db = DB::open(&options, path1.as_ref()).unwrap();
+        db.put_default("key", "value").unwrap();
+        db.flush(&FlushOptions::new()).unwrap();
+        let txn_db =
+            TransactionDB::open(&options, &TransactionDBOptions::new(), path2.as_ref()).unwrap();
+        drop(options);
+
+        let usage = cache.get_usage();
+        db.get_default("key").unwrap().unwrap();
+        assert!(cache.get_usage() > usage);
+        let usage = cache.get_usage();
+        txn_db.get_default("key").unwrap().unwrap();
+        assert!(cache.get_usage() > usage);
+
+        cache.set_capacity(1 << 10);
+        assert_eq!(cache.get_capacity(), 1 << 10);
+    }
+}
@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::options::{default_read_options, default_write_options};
+use crate::{Bytes, ColumnFamily, Error, Options, Result, WriteBatch, WriteOptions, DB};
+
+const NIL: usize = usize::MAX;
+
+// A DB with an in-process LRU cache of key lookups in front of get. Writes go
+// through the wrapper and invalidate the cache, so the DB must not be written
+// to by other means while it's open. Misses are cached too. Entries are keyed
+// by column family and key.
+pub struct CachedDb {
+    db: DB,
+    // the id of the default column family
+    default: usize,
+    cache: Mutex<Lru>,
+}
+
+impl CachedDb {
+    // capacity is the total size in bytes of the cached keys and values
+    pub fn open(options: &Options, path: impl AsRef<Path>, capacity: usize) -> Result<Self> {
+        Self::open_cf(options, path, &[], capacity)
+    }
+
+    // see DB::open_cf
+    pub fn open_cf(
+        options: &Options,
+        path: impl AsRef<Path>,
+        column_families: &[(&str, &Options)],
+        capacity: usize,
+    ) -> Result<Self> {
+        let db = DB::open_cf(options, path, column_families)?;
+        let default = match db.cf_handle("default") {
+            Some(cf) => cf_id(&db, &cf)?,
+            None => return Err(Error::invalid_argument("no default column family")),
+        };
+        Ok(Self {
+            db,
+            default,
+            cache: Mutex::new(Lru::new(capacity)),
+        })
+    }
+
+    pub fn cf_handle(&self, name: &str) -> Option<ColumnFamily<'_>> {
+        self.db.cf_handle(name)
+    }
+
+    pub fn get(&self, key: impl AsRef<[u8]>) -> Result<Option<Vec<u8>>> {
+        self.cached_get(self.default, key.as_ref(), |key| {
+            self.db.get(default_read_options(), key)
+        })
+    }
+
+    pub fn get_cf(&self, cf: &ColumnFamily, key: impl AsRef<[u8]>) -> Result<Option<Vec<u8>>> {
+        self.cached_get(cf_id(&self.db, cf)?, key.as_ref(), |key| {
+            self.db.get_cf(default_read_options(), cf, key)
+        })
+    }
+
+    fn cached_get(
+        &self,
+        cf: usize,
+        key: &[u8],
+        get: impl FnOnce(&[u8]) -> Result<Option<Bytes>>,
+    ) -> Result<Option<Vec<u8>>> {
+        let epoch = {
+            let mut cache = self.cache();
+            if let Some(value) = cache.get(cf, key) {
+                return Ok(value.map(|v| v.to_vec()));
+            }
+            cache.epoch
+        };
+        let value = get(key)?.map(Vec::from);
+        let mut cache = self.cache();
+        // a write since the read started may have invalidated what we got
+        if cache.epoch == epoch {
+            cache.insert(cf, key, value.as_deref());
+        }
+        Ok(value)
+    }
+
+    pub fn put(
+        &self,
+        options: &WriteOptions,
+        key: impl AsRef<[u8]>,
+        value: impl AsRef<[u8]>,
+    ) -> Result<()> {
+        let key = key.as_ref();
+        let result = self.db.put(options, key, value);
+        self.cache().remove(self.default, key);
+        result
+    }
+
+    pub fn put_cf(
+        &self,
+        options: &WriteOptions,
+        cf: &ColumnFamily,
+        key: impl AsRef<[u8]>,
+        value: impl AsRef<[u8]>,
+    ) -> Result<()> {
+        let id = cf_id(&self.db, cf)?;
+        let key = key.as_ref();
+        let result = self.db.put_cf(options, cf, key, value);
+        self.cache().remove(id, key);
+        result
+    }
+
+    pub fn delete(&self, options: &WriteOptions, key: impl AsRef<[u8]>) -> Result<()> {
+        let key = key.as_ref();
+        let result = self.db.delete(options, key);
+        self.cache().remove(self.default, key);
+        result
+    }
+
+    pub fn delete_cf(
+        &self,
+        options: &WriteOptions,
+        cf: &ColumnFamily,
+        key: impl AsRef<[u8]>,
+    ) -> Result<()> {
+        let id = cf_id(&self.db, cf)?;
+        let key = key.as_ref();
+        let result = self.db.delete_cf(options, cf, key);
+        self.cache().remove(id, key);
+        result
+    }
+
+    // Clears the whole cache.
+    pub fn write(&self, options: &WriteOptions, batch: &WriteBatch) -> Result<()> {
+        let result = self.db.write(options, batch);
+        self.cache().clear();
+        result
+    }
+
+    pub fn put_default(&self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) -> Result<()> {
+        self.put(default_write_options(), key, value)
+    }
+
+    pub fn delete_default(&self, key: impl AsRef<[u8]>) -> Result<()> {
+        self.delete(default_write_options(), key)
+    }
+
+    fn cache(&self) -> MutexGuard<'_, Lru> {
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+// The address of the family's handle, which stays the same while the db is
+// open since CachedDb can't drop families.
+fn cf_id(db: &DB, cf: &ColumnFamily) -> Result<usize> {
+    Ok(cf.as_ptr_for(db.inner)? as usize)
+}
+
+struct Node {
+    cf: usize,
+    key: Box<[u8]>,
+    value: Option<Box<[u8]>>,
+    prev: usize,
+    next: usize,
+}
+
+impl Node {
+    fn size(&self) -> usize {
+        self.key.len() + self.value.as_ref().map_or(0, |v| v.len())
+    }
+}
+
+// Nodes live in a Vec and are linked by index, most recently used first.
+struct Lru {
+    capacity: usize,
+    size: usize,
+    // bumped on every invalidation
+    epoch: u64,
+    index: HashMap<(usize, Box<[u8]>), usize>,
+    nodes: Vec<Node>,
+    free: Vec<usize>,
+    head: usize,
+    tail: usize,
+}
+
+impl Lru {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            size: 0,
+            epoch: 0,
+            index: HashMap::new(),
+            nodes: Vec::new(),
+            free: Vec::new(),
+            head: NIL,
+            tail: NIL,
+        }
+    }
+
+    fn get(&mut self, cf: usize, key: &[u8]) -> Option<Option<&[u8]>> {
+        let i = *self.index.get(&(cf, key.into()))?;
+        self.unlink(i);
+        self.push_front(i);
+        Some(self.nodes[i].value.as_deref())
+    }
+
+    fn insert(&mut self, cf: usize, key: &[u8], value: Option<&[u8]>) {
+        self.remove_node(cf, key);
+        let node = Node {
+            cf,
+            key: key.into(),
+            value: value.map(Into::into),
+            prev: NIL,
+            next: NIL,
+        };
+        if node.size() > self.capacity {
+            return;
+        }
+        self.size += node.size();
+        let i = match self.free.pop() {
+            Some(i) => {
+                self.nodes[i] = node;
+                i
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+        self.index.insert((cf, key.into()), i);
+        self.push_front(i);
+        while self.size > self.capacity {
+            let tail = &self.nodes[self.tail];
+            let (cf, key) = (tail.cf, tail.key.clone());
+            self.remove_node(cf, &key);
+        }
+    }
+
+    fn remove(&mut self, cf: usize, key: &[u8]) {
+        self.remove_node(cf, key);
+        self.epoch += 1;
+    }
+
+    fn clear(&mut self) {
+        self.index.clear();
+        self.nodes.clear();
+        self.free.clear();
+        self.head = NIL;
+        self.tail = NIL;
+        self.size = 0;
+        self.epoch += 1;
+    }
+
+    fn remove_node(&mut self, cf: usize, key: &[u8]) {
+        if let Some(i) = self.index.remove(&(cf, key.into())) {
+            self.unlink(i);
+            self.size -= self.nodes[i].size();
+            self.nodes[i].key = Box::default();
+            self.nodes[i].value = None;
+            self.free.push(i);
+        }
+    }
+
+    fn unlink(&mut self, i: usize) {
+        let (prev, next) = (self.nodes[i].prev, self.nodes[i].next);
+        match prev {
+            NIL => self.head = next,
+            p => self.nodes[p].next = next,
+        }
+        match next {
+            NIL => self.tail = prev,
+            n => self.nodes[n].prev = prev,
+        }
+    }
+
+    fn push_front(&mut self, i: usize) {
+        self.nodes[i].prev = NIL;
+        self.nodes[i].next = self.head;
+        match self.head {
+            NIL => self.tail = i,
+            h => self.nodes[h].prev = i,
+        }
+        self.head = i;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::options::tests::DBPath;
+    use crate::{CachedDb, Options, WriteBatch, WriteOptions};
+
+    use super::Lru;
+
+    #[test]
+    fn test_lru_eviction() {
+        let mut lru = Lru::new(4);
+        lru.insert(0, b"a", Some(b"1"));
+        lru.insert(0, b"b", Some(b"2"));
+        assert_eq!(lru.get(0, b"a"), Some(Some(&b"1"[..])));
+        lru.insert(0, b"c", None);
+        // b was least recently used
+        assert_eq!(lru.get(0, b"b"), None);
+        assert_eq!(lru.get(0, b"a"), Some(Some(&b"1"[..])));
+        assert_eq!(lru.get(0, b"c"), Some(None));
+        assert_eq!(lru.size, 3);
+        lru.insert(0, b"toolarge", None);
+        assert_eq!(lru.get(0, b"toolarge"), None);
+    }
+
+    #[test]
+    fn test_cached_db() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = CachedDb::open(&options, path.as_ref(), 1024).unwrap();
+
+        assert_eq!(db.get("key").unwrap(), None);
+        db.put_default("key", "value").unwrap();
+        assert_eq!(db.get("key").unwrap().unwrap(), b"value");
+        assert_eq!(db.get("key").unwrap().unwrap(), b"value");
+
+        let mut batch = WriteBatch::new();
+        batch.put("key", "batch");
+        db.write(&WriteOptions::new(), &batch).unwrap();
+        assert_eq!(db.get("key").unwrap().unwrap(), b"batch");
+
+        db.delete_default("key").unwrap();
+        assert_eq!(db.get("key").unwrap(), None);
+    }
+
+    #[test]
+    fn test_cached_db_column_families() {
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_create_missing_column_families(true);
+        let path = DBPath::new();
+        let cf_options = Options::new();
+        let db = CachedDb::open_cf(&options, path.as_ref(), &[("a", &cf_options)], 1024).unwrap();
+        let a = db.cf_handle("a").unwrap();
+        let default = db.cf_handle("default").unwrap();
+
+        let write_op = WriteOptions::new();
+        db.put_default("key", "default").unwrap();
+        db.put_cf(&write_op, &a, "key", "a").unwrap();
+        assert_eq!(db.get("key").unwrap().unwrap(), b"default");
+        assert_eq!(db.get_cf(&a, "key").unwrap().unwrap(), b"a");
+        assert_eq!(db.get_cf(&default, "key").unwrap().unwrap(), b"default");
+
+        // the default family shares its entries with get
+        db.put_cf(&write_op, &default, "key", "changed").unwrap();
+        assert_eq!(db.get("key").unwrap().unwrap(), b"changed");
+        db.delete_cf(&write_op, &a, "key").unwrap();
+        assert_eq!(db.get_cf(&a, "key").unwrap(), None);
+        assert_eq!(db.get("key").unwrap().unwrap(), b"changed");
+    }
+}
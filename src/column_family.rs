@@ -0,0 +1,82 @@
+use std::fmt::{self, Debug, Formatter};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use librocksdb_sys::*;
+
+use crate::{Error, Result, DB};
+
+// A column family of an open db. Handles are reference counted: drop_cf only
+// marks the family dropped in RocksDB, and the handle is destroyed once the
+// last ColumnFamily for it goes away. The lifetime ties the handles to the
+// db, so that always happens before the db is closed. Handles remember their
+// db and are rejected by any other.
+#[derive(Clone)]
+pub struct ColumnFamily<'a> {
+    inner: Arc<RawColumnFamily>,
+    _marker: PhantomData<&'a DB>,
+}
+
+pub(crate) struct RawColumnFamily {
+    inner: *mut rocksdb_column_family_handle_t,
+    db: *mut rocksdb_t,
+    name: String,
+}
+
+impl RawColumnFamily {
+    pub(crate) fn new(
+        inner: *mut rocksdb_column_family_handle_t,
+        db: *mut rocksdb_t,
+        name: String,
+    ) -> Arc<Self> {
+        Arc::new(Self { inner, db, name })
+    }
+
+    pub(crate) fn as_ptr(&self) -> *mut rocksdb_column_family_handle_t {
+        self.inner
+    }
+}
+
+impl Drop for RawColumnFamily {
+    fn drop(&mut self) {
+        unsafe { rocksdb_column_family_handle_destroy(self.inner) }
+    }
+}
+
+unsafe impl Send for RawColumnFamily {}
+
+unsafe impl Sync for RawColumnFamily {}
+
+impl<'a> ColumnFamily<'a> {
+    pub(crate) fn new(inner: Arc<RawColumnFamily>) -> Self {
+        Self {
+            inner,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    // the raw handle, if the family belongs to db
+    pub(crate) fn as_ptr_for(
+        &self,
+        db: *mut rocksdb_t,
+    ) -> Result<*mut rocksdb_column_family_handle_t> {
+        if self.inner.db == db {
+            Ok(self.inner.inner)
+        } else {
+            Err(Error::invalid_argument(&format!(
+                "column family {} belongs to another db",
+                self.name()
+            )))
+        }
+    }
+}
+
+impl<'a> Debug for ColumnFamily<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ColumnFamily").field(&self.name()).finish()
+    }
+}
@@ -0,0 +1,190 @@
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int, c_uchar, c_void};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::slice;
+use std::sync::Arc;
+
+use librocksdb_sys::*;
+
+use crate::name_to_cstring;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompactionDecision {
+    Keep,
+    Remove,
+    // keeps the key with a new value
+    Change(Vec<u8>),
+}
+
+// Called by compactions for every key they keep, to drop or rewrite it.
+// Filters run on the compaction threads, and see every version of a key
+// regardless of snapshots.
+pub trait CompactionFilter: Send + Sync + 'static {
+    fn name(&self) -> &str;
+
+    fn filter(&self, level: u32, key: &[u8], value: &[u8]) -> CompactionDecision;
+}
+
+struct State {
+    name: CString,
+    filter: Box<dyn CompactionFilter>,
+}
+
+// RocksDB creates a filter per compaction and calls it from one thread. The
+// filter keeps the last changed value alive, as RocksDB copies it only after
+// the callback returns.
+struct FilterState {
+    state: Arc<State>,
+    new_value: Vec<u8>,
+}
+
+// The options take ownership of the returned factory, which gives every
+// compaction its own filter over the same state.
+pub(crate) fn create(filter: impl CompactionFilter) -> *mut rocksdb_compactionfilterfactory_t {
+    let state = Box::new(Arc::new(State {
+        name: name_to_cstring(filter.name()),
+        filter: Box::new(filter),
+    }));
+    unsafe {
+        rocksdb_compactionfilterfactory_create(
+            Box::into_raw(state) as _,
+            Some(factory_destructor),
+            Some(create_filter),
+            Some(factory_name),
+        )
+    }
+}
+
+unsafe extern "C" fn factory_destructor(state: *mut c_void) {
+    drop(Box::from_raw(state as *mut Arc<State>));
+}
+
+unsafe extern "C" fn factory_name(state: *mut c_void) -> *const c_char {
+    let state = &*(state as *const Arc<State>);
+    state.name.as_ptr()
+}
+
+unsafe extern "C" fn create_filter(
+    state: *mut c_void,
+    _context: *mut rocksdb_compactionfiltercontext_t,
+) -> *mut rocksdb_compactionfilter_t {
+    let state = Box::new(FilterState {
+        state: (*(state as *const Arc<State>)).clone(),
+        new_value: Vec::new(),
+    });
+    rocksdb_compactionfilter_create(
+        Box::into_raw(state) as _,
+        Some(filter_destructor),
+        Some(filter),
+        Some(filter_name),
+    )
+}
+
+unsafe extern "C" fn filter_destructor(state: *mut c_void) {
+    drop(Box::from_raw(state as *mut FilterState));
+}
+
+unsafe extern "C" fn filter_name(state: *mut c_void) -> *const c_char {
+    let state = &*(state as *const FilterState);
+    state.state.name.as_ptr()
+}
+
+unsafe extern "C" fn filter(
+    state: *mut c_void,
+    level: c_int,
+    key: *const c_char,
+    key_length: usize,
+    existing_value: *const c_char,
+    value_length: usize,
+    new_value: *mut *mut c_char,
+    new_value_length: *mut usize,
+    value_changed: *mut c_uchar,
+) -> c_uchar {
+    let state = &mut *(state as *mut FilterState);
+    let key = slice::from_raw_parts(key as *const u8, key_length);
+    let value = slice::from_raw_parts(existing_value as *const u8, value_length);
+    // a panic must not unwind into RocksDB, the key is kept
+    let decision = catch_unwind(AssertUnwindSafe(|| {
+        state.state.filter.filter(level as u32, key, value)
+    }));
+    match decision.unwrap_or(CompactionDecision::Keep) {
+        CompactionDecision::Keep => 0,
+        CompactionDecision::Remove => 1,
+        CompactionDecision::Change(value) => {
+            state.new_value = value;
+            *new_value = state.new_value.as_mut_ptr() as *mut c_char;
+            *new_value_length = state.new_value.len();
+            *value_changed = 1;
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::options::tests::DBPath;
+    use crate::{CompactionDecision, CompactionFilter, Options, DB};
+
+    struct Cleanup;
+
+    impl CompactionFilter for Cleanup {
+        fn name(&self) -> &str {
+            "Cleanup"
+        }
+
+        fn filter(&self, _level: u32, key: &[u8], value: &[u8]) -> CompactionDecision {
+            if key.starts_with(b"tmp:") {
+                CompactionDecision::Remove
+            } else if key.starts_with(b"upper:") {
+                CompactionDecision::Change(value.to_ascii_uppercase())
+            } else {
+                CompactionDecision::Keep
+            }
+        }
+    }
+
+    #[test]
+    fn test_compaction_filter() {
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_compaction_filter(Cleanup);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        drop(options);
+
+        db.put_default("tmp:1", "a").unwrap();
+        db.put_default("upper:1", "abc").unwrap();
+        db.put_default("other", "abc").unwrap();
+        db.compact_range(None, None);
+
+        assert!(db.get_default("tmp:1").unwrap().is_none());
+        assert_eq!(db.get_default("upper:1").unwrap().unwrap(), b"ABC");
+        assert_eq!(db.get_default("other").unwrap().unwrap(), b"abc");
+    }
+
+    struct KeepAll;
+
+    impl CompactionFilter for KeepAll {
+        fn name(&self) -> &str {
+            "Keep\0All"
+        }
+
+        fn filter(&self, _level: u32, _key: &[u8], _value: &[u8]) -> CompactionDecision {
+            CompactionDecision::Keep
+        }
+    }
+
+    #[test]
+    fn test_name_with_nul() {
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_compaction_filter(KeepAll);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        db.put_default("key", "value").unwrap();
+        db.compact_range(None, None);
+        assert_eq!(db.get_default("key").unwrap().unwrap(), b"value");
+    }
+}
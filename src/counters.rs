@@ -0,0 +1,65 @@
+use std::path::Path;
+
+use librocksdb_sys::*;
+
+use crate::options::{default_read_options, default_write_options};
+use crate::{Error, Options, Result, DB};
+
+// Counters are stored as 8-byte little-endian integers, the encoding of
+// rocksdb's built-in uint64add merge operator.
+pub struct Counters {
+    db: DB,
+}
+
+impl Counters {
+    pub fn open(options: &Options, path: impl AsRef<Path>) -> Result<Self> {
+        let options = options.clone();
+        unsafe { rocksdb_options_set_uint64add_merge_operator(options.inner) };
+        Ok(Self {
+            db: DB::open(&options, path)?,
+        })
+    }
+
+    // wraps around on overflow
+    pub fn incr(&self, key: impl AsRef<[u8]>, delta: u64) -> Result<()> {
+        self.db.merge_default(key, delta.to_le_bytes())
+    }
+
+    pub fn get(&self, key: impl AsRef<[u8]>) -> Result<u64> {
+        match self.db.get(default_read_options(), key)? {
+            Some(value) => match (*value).try_into() {
+                Ok(bytes) => Ok(u64::from_le_bytes(bytes)),
+                Err(_) => Err(Error::corruption("counter is not 8 bytes")),
+            },
+            None => Ok(0),
+        }
+    }
+
+    pub fn reset(&self, key: impl AsRef<[u8]>) -> Result<()> {
+        self.db.delete(default_write_options(), key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::options::tests::DBPath;
+    use crate::{Counters, Options};
+
+    #[test]
+    fn test_counters() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let counters = Counters::open(&options, path.as_ref()).unwrap();
+
+        assert_eq!(counters.get("hits").unwrap(), 0);
+        counters.incr("hits", 1).unwrap();
+        counters.incr("hits", 41).unwrap();
+        assert_eq!(counters.get("hits").unwrap(), 42);
+
+        counters.reset("hits").unwrap();
+        assert_eq!(counters.get("hits").unwrap(), 0);
+        counters.incr("hits", 3).unwrap();
+        assert_eq!(counters.get("hits").unwrap(), 3);
+    }
+}
@@ -1,43 +1,365 @@
-use std::ffi::CString;
-use std::os::raw::c_char;
-use std::ptr::null_mut;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_uchar};
+use std::path::Path;
+use std::ptr::{null, null_mut};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, Instant};
 
 use librocksdb_sys::*;
 
-use crate::options::{Options, WriteOptions};
-use crate::snapshot::{OwnedSnapshot, ReleaseSnapshot};
-use crate::{Bytes, Error, FlushOptions, ReadOptions, Result, WriteBatch};
+use crate::column_family::RawColumnFamily;
+use crate::options::{default_read_options, default_write_options, Options, WriteOptions};
+use crate::registry::{self, Registration};
+use crate::snapshot::{OwnedSnapshot, ReleaseSnapshot, SharedSnapshot};
+use crate::trace::Op;
+use crate::{free, path_to_cstring, prefix_successor};
+use crate::{
+    Bytes, ColumnFamily, CompactRangeOptions, Direction, Error, ErrorKind, FlushOptions,
+    IngestExternalFileOptions, Iter, KeyRange, PinnableSlice, ReadOptions, Result, ScanRate,
+    SharedIterator, SubCode, TableProperties, Throttled, WriteBatch, WriteBatchWithIndex,
+};
 
 pub struct DB {
     pub(crate) inner: *mut rocksdb_t,
+    // None for read-only instances, which don't lock the db
+    pub(crate) _registration: Option<Registration>,
+    // the families opened with open_cf or created with create_cf
+    pub(crate) column_families: RwLock<ColumnFamilies>,
 }
 
 impl DB {
-    pub fn open(options: &Options, name: &str) -> Result<Self> {
-        let name = CString::new(name).unwrap();
+    // Fails with ErrorKind::Busy if the path is already open in this process.
+    pub fn open(options: &Options, path: impl AsRef<Path>) -> Result<Self> {
+        Self::open_cf(options, path, &[])
+    }
+
+    // Opens the db with the given column families, each with its own options.
+    // The default column family is opened with `options` unless it is listed.
+    // All existing families must be listed, missing ones are only created with
+    // create_missing_column_families. The handles are available from cf_handle.
+    pub fn open_cf(
+        options: &Options,
+        path: impl AsRef<Path>,
+        column_families: &[(&str, &Options)],
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        let ((inner, column_families), registration) = registry::register(path, || {
+            open_column_families(options, path, column_families)
+        })?;
         Ok(Self {
-            inner: ffi!(rocksdb_open(options.inner, name.as_ptr())),
+            inner,
+            _registration: Some(registration),
+            column_families: RwLock::new(column_families),
         })
     }
 
-    pub fn destroy(options: &Options, name: &str) -> Result<()> {
-        let name = CString::new(name).unwrap();
-        Ok(ffi!(rocksdb_destroy_db(options.inner, name.as_ptr())))
+    pub fn list_cf(options: &Options, path: impl AsRef<Path>) -> Result<Vec<String>> {
+        let path = path_to_cstring(path.as_ref())?;
+        let mut len = 0;
+        let list = ffi!(rocksdb_list_column_families(
+            options.inner,
+            path.as_ptr(),
+            &mut len
+        ));
+        let names = (0..len)
+            .map(|i| {
+                unsafe { CStr::from_ptr(*list.add(i)) }
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        unsafe { rocksdb_list_column_families_destroy(list, len) };
+        Ok(names)
+    }
+
+    pub fn create_cf(&self, name: &str, options: &Options) -> Result<ColumnFamily<'_>> {
+        let c_name = cf_name_to_cstring(name)?;
+        let mut column_families = self
+            .column_families
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let handle = ffi!(rocksdb_create_column_family(
+            self.inner,
+            options.inner,
+            c_name.as_ptr()
+        ));
+        let raw = RawColumnFamily::new(handle, self.inner, name.to_string());
+        column_families.insert(name.to_string(), raw.clone());
+        Ok(ColumnFamily::new(raw))
+    }
+
+    // Handles still held keep working for reads that are already in progress,
+    // the data is removed once they are all dropped.
+    pub fn drop_cf(&self, name: &str) -> Result<()> {
+        let mut column_families = self
+            .column_families
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let raw = column_families.get(name).ok_or_else(|| {
+            Error::invalid_argument(&format!("column family {} is not open", name))
+        })?;
+        ffi!(rocksdb_drop_column_family(self.inner, raw.as_ptr()));
+        column_families.remove(name);
+        Ok(())
+    }
+
+    // None if the family was not opened with open_cf or created with
+    // create_cf, or has been dropped. The default family is always available.
+    pub fn cf_handle(&self, name: &str) -> Option<ColumnFamily<'_>> {
+        self.column_families
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(name)
+            .map(|raw| ColumnFamily::new(raw.clone()))
+    }
+
+    pub fn create_iterator_cf(
+        &self,
+        options: &ReadOptions,
+        cf: &ColumnFamily,
+    ) -> Result<crate::Iterator<'_>> {
+        let cf = cf.as_ptr_for(self.inner)?;
+        let _op = Op::new("create_iterator_cf", 0, 0);
+        Ok(crate::Iterator::new(unsafe {
+            rocksdb_create_iterator_cf(self.inner, options.inner, cf)
+        }))
+    }
+
+    // Iterators over several column families that all read from the same
+    // point in time.
+    pub fn create_iterators(
+        &self,
+        options: &ReadOptions,
+        cfs: &[&ColumnFamily],
+    ) -> Result<Vec<crate::Iterator<'_>>> {
+        let mut handles = cfs
+            .iter()
+            .map(|cf| cf.as_ptr_for(self.inner))
+            .collect::<Result<Vec<_>>>()?;
+        let _op = Op::new("create_iterators", 0, 0);
+        let mut iterators = vec![null_mut(); cfs.len()];
+        ffi!(rocksdb_create_iterators(
+            self.inner,
+            options.inner,
+            handles.as_mut_ptr(),
+            iterators.as_mut_ptr(),
+            cfs.len()
+        ));
+        Ok(iterators.into_iter().map(crate::Iterator::new).collect())
+    }
+
+    pub fn get_cf(
+        &self,
+        options: &ReadOptions,
+        cf: &ColumnFamily,
+        key: impl AsRef<[u8]>,
+    ) -> Result<Option<Bytes>> {
+        let cf = cf.as_ptr_for(self.inner)?;
+        let mut len: usize = 0;
+        let key = key.as_ref();
+        let op = Op::new("get_cf", key.len(), 0);
+        let value = ffi!(rocksdb_get_cf(
+            self.inner,
+            options.inner,
+            cf,
+            key.as_ptr() as _,
+            key.len(),
+            &mut len
+        ));
+        if !value.is_null() {
+            op.value_size(len);
+            Ok(Some(Bytes::new(value, len)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    // each key is read from the column family paired with it
+    pub fn multi_get_cf<K: AsRef<[u8]>>(
+        &self,
+        options: &ReadOptions,
+        keys: &[(&ColumnFamily, K)],
+    ) -> Vec<Result<Option<Bytes>>> {
+        let num_keys = keys.len();
+        let mut cf_list = Vec::with_capacity(num_keys);
+        let mut keys_list = Vec::with_capacity(num_keys);
+        let mut keys_list_sizes = Vec::with_capacity(num_keys);
+        let mut values_list: Vec<*mut c_char> = vec![null_mut(); num_keys];
+        let mut values_list_sizes: Vec<usize> = vec![0; num_keys];
+        let mut errs: Vec<*mut c_char> = vec![null_mut(); num_keys];
+
+        for (cf, key) in keys {
+            let key = key.as_ref();
+            match cf.as_ptr_for(self.inner) {
+                Ok(cf) => cf_list.push(cf as *const _),
+                // a handle of another db fails every key
+                Err(e) => return (0..num_keys).map(|_| Err(e.clone())).collect(),
+            }
+            keys_list.push(key.as_ptr() as *const c_char);
+            keys_list_sizes.push(key.len());
+        }
+
+        unsafe {
+            rocksdb_multi_get_cf(
+                self.inner,
+                options.inner,
+                cf_list.as_ptr(),
+                num_keys,
+                keys_list.as_ptr(),
+                keys_list_sizes.as_ptr(),
+                values_list.as_mut_ptr(),
+                values_list_sizes.as_mut_ptr(),
+                errs.as_mut_ptr(),
+            );
+        }
+
+        multi_get_results(values_list, values_list_sizes, errs)
+    }
+
+    pub fn put_cf(
+        &self,
+        options: &WriteOptions,
+        cf: &ColumnFamily,
+        key: impl AsRef<[u8]>,
+        value: impl AsRef<[u8]>,
+    ) -> Result<()> {
+        let cf = cf.as_ptr_for(self.inner)?;
+        let key = key.as_ref();
+        let value = value.as_ref();
+        let _op = Op::new("put_cf", key.len(), value.len());
+        Ok(ffi!(rocksdb_put_cf(
+            self.inner,
+            options.inner,
+            cf,
+            key.as_ptr() as _,
+            key.len(),
+            value.as_ptr() as _,
+            value.len()
+        )))
+    }
+
+    pub fn delete_cf(
+        &self,
+        options: &WriteOptions,
+        cf: &ColumnFamily,
+        key: impl AsRef<[u8]>,
+    ) -> Result<()> {
+        let cf = cf.as_ptr_for(self.inner)?;
+        let key = key.as_ref();
+        let _op = Op::new("delete_cf", key.len(), 0);
+        Ok(ffi!(rocksdb_delete_cf(
+            self.inner,
+            options.inner,
+            cf,
+            key.as_ptr() as _,
+            key.len()
+        )))
+    }
+
+    // Returns the instance already opened with open_shared for the same path,
+    // or opens a new one. Fails with ErrorKind::Busy if the path was opened
+    // with open.
+    pub fn open_shared(options: &Options, path: impl AsRef<Path>) -> Result<Arc<Self>> {
+        let path = path.as_ref();
+        registry::register_shared(path, || open_column_families(options, path, &[]))
+    }
+
+    pub fn destroy(options: &Options, path: impl AsRef<Path>) -> Result<()> {
+        let path = path_to_cstring(path.as_ref())?;
+        Ok(ffi!(rocksdb_destroy_db(options.inner, path.as_ptr())))
     }
 
-    pub fn repair(options: &Options, name: &str) -> Result<()> {
-        let name = CString::new(name).unwrap();
+    pub fn repair(options: &Options, path: impl AsRef<Path>) -> Result<()> {
+        let path = path_to_cstring(path.as_ref())?;
 
-        Ok(ffi!(rocksdb_repair_db(options.inner, name.as_ptr())))
+        Ok(ffi!(rocksdb_repair_db(options.inner, path.as_ptr())))
+    }
+
+    // Repairs and retries once if the open fails because of corruption or
+    // missing files. The report holds the error that triggered the repair.
+    pub fn open_or_repair(
+        options: &Options,
+        path: impl AsRef<Path>,
+    ) -> Result<(Self, Option<RepairReport>)> {
+        let path = path.as_ref();
+        match Self::open(options, path) {
+            Ok(db) => Ok((db, None)),
+            Err(error) if is_repairable(&error) => {
+                Self::repair(options, path)?;
+                let db = Self::open(options, path)?;
+                Ok((db, Some(RepairReport { open_error: error })))
+            }
+            Err(error) => Err(error),
+        }
     }
 
     pub fn create_iterator(&self, options: &ReadOptions) -> crate::Iterator {
+        let _op = Op::new("create_iterator", 0, 0);
         crate::Iterator::new(unsafe { rocksdb_create_iterator(self.inner, options.inner) })
     }
 
+    pub fn iter(&self, options: &ReadOptions) -> Iter<'_> {
+        let mut iter = self.create_iterator(options);
+        iter.seek_to_first();
+        iter.into_iter()
+    }
+
+    pub fn iter_rev(&self, options: &ReadOptions) -> Iter<'_> {
+        let mut iter = self.create_iterator(options);
+        iter.seek_to_last();
+        iter.into_iter_with(Direction::Reverse)
+    }
+
+    pub fn range<'b>(&'b self, options: ReadOptions<'b>, range: impl KeyRange) -> Iter<'b> {
+        Iter::range(options, range, |options| self.create_iterator(options))
+    }
+
+    // For background jobs: reads at most `rate` and doesn't fill the block
+    // cache, so foreground reads keep their cached blocks.
+    pub fn range_throttled(&self, range: impl KeyRange, rate: ScanRate) -> Throttled<Iter<'_>> {
+        let mut options = ReadOptions::new();
+        options.set_fill_cache(false);
+        self.range(options, range).throttled(rate)
+    }
+
+    pub fn create_shared_iterator(
+        self: &Arc<Self>,
+        options: ReadOptions<'static>,
+    ) -> SharedIterator<Self> {
+        let inner = unsafe { rocksdb_create_iterator(self.inner, options.inner) };
+        SharedIterator::new(inner, options, self.clone())
+    }
+
+    #[cfg(feature = "async")]
+    pub fn stream_range(
+        self: &Arc<Self>,
+        range: impl KeyRange + Send + 'static,
+        chunk_size: usize,
+    ) -> crate::ScanStream {
+        crate::ScanStream::spawn(self.clone(), range, chunk_size)
+    }
+
+    #[cfg(feature = "async")]
+    pub fn stream_prefix(
+        self: &Arc<Self>,
+        prefix: impl AsRef<[u8]>,
+        chunk_size: usize,
+    ) -> crate::ScanStream {
+        use std::ops::Bound;
+
+        let prefix = prefix.as_ref();
+        let end = match prefix_successor(prefix) {
+            Some(end) => Bound::Excluded(end),
+            None => Bound::Unbounded,
+        };
+        self.stream_range((Bound::Included(prefix.to_vec()), end), chunk_size)
+    }
+
     pub fn get(&self, options: &ReadOptions, key: impl AsRef<[u8]>) -> Result<Option<Bytes>> {
         let mut len: usize = 0;
         let key = key.as_ref();
+        let op = Op::new("get", key.len(), 0);
         let value = ffi!(rocksdb_get(
             self.inner,
             options.inner,
@@ -46,12 +368,33 @@ impl DB {
             &mut len
         ));
         if !value.is_null() {
+            op.value_size(len);
             Ok(Some(Bytes::new(value, len)))
         } else {
             Ok(None)
         }
     }
 
+    pub fn get_pinned(
+        &self,
+        options: &ReadOptions,
+        key: impl AsRef<[u8]>,
+    ) -> Result<Option<PinnableSlice<'_>>> {
+        let key = key.as_ref();
+        let _op = Op::new("get_pinned", key.len(), 0);
+        let value = ffi!(rocksdb_get_pinned(
+            self.inner,
+            options.inner,
+            key.as_ptr() as _,
+            key.len()
+        ));
+        if !value.is_null() {
+            Ok(Some(PinnableSlice::new(value)))
+        } else {
+            Ok(None)
+        }
+    }
+
     pub fn multi_get(
         &self,
         options: &ReadOptions,
@@ -83,22 +426,15 @@ impl DB {
             );
         }
 
-        let mut ret = Vec::with_capacity(num_keys);
-        for i in 0..num_keys {
-            let err = errs[i];
-            let v = if err.is_null() {
-                let value = values_list[i];
-                if !value.is_null() {
-                    Ok(Some(Bytes::new(value, values_list_sizes[i])))
-                } else {
-                    Ok(None)
-                }
-            } else {
-                Err(Error::new(err))
-            };
-            ret.push(v);
-        }
-        ret
+        multi_get_results(values_list, values_list_sizes, errs)
+    }
+
+    pub fn try_multi_get(
+        &self,
+        options: &ReadOptions,
+        keys: &[impl AsRef<[u8]>],
+    ) -> Result<Vec<Option<Bytes>>> {
+        self.multi_get(options, keys).into_iter().collect()
     }
 
     pub fn put(
@@ -109,6 +445,7 @@ impl DB {
     ) -> Result<()> {
         let key = key.as_ref();
         let value = value.as_ref();
+        let _op = Op::new("put", key.len(), value.len());
         Ok(ffi!(rocksdb_put(
             self.inner,
             options.inner,
@@ -119,12 +456,47 @@ impl DB {
         )))
     }
 
+    // needs a merge operator set on the options
+    pub fn merge(
+        &self,
+        options: &WriteOptions,
+        key: impl AsRef<[u8]>,
+        value: impl AsRef<[u8]>,
+    ) -> Result<()> {
+        let key = key.as_ref();
+        let value = value.as_ref();
+        let _op = Op::new("merge", key.len(), value.len());
+        Ok(ffi!(rocksdb_merge(
+            self.inner,
+            options.inner,
+            key.as_ptr() as _,
+            key.len(),
+            value.as_ptr() as _,
+            value.len()
+        )))
+    }
+
     pub fn write(&self, options: &WriteOptions, batch: &WriteBatch) -> Result<()> {
+        let _op = Op::new("write", 0, 0);
         Ok(ffi!(rocksdb_write(self.inner, options.inner, batch.inner)))
     }
 
+    pub fn write_with_index(
+        &self,
+        options: &WriteOptions,
+        batch: &WriteBatchWithIndex,
+    ) -> Result<()> {
+        let _op = Op::new("write_with_index", 0, 0);
+        Ok(ffi!(rocksdb_write_writebatch_wi(
+            self.inner,
+            options.inner,
+            batch.inner
+        )))
+    }
+
     pub fn delete(&self, options: &WriteOptions, key: impl AsRef<[u8]>) -> Result<()> {
         let key = key.as_ref();
+        let _op = Op::new("delete", key.len(), 0);
         Ok(ffi!(rocksdb_delete(
             self.inner,
             options.inner,
@@ -133,83 +505,1202 @@ impl DB {
         )))
     }
 
-    pub fn flush(&self, options: &FlushOptions) -> Result<()> {
-        Ok(ffi!(rocksdb_flush(self.inner, options.inner)))
+    // Deletes [begin, end) with a single range tombstone. The C API has no
+    // DeleteRange for the default column family without a handle, it goes
+    // through a batch instead.
+    pub fn delete_range(
+        &self,
+        options: &WriteOptions,
+        begin: impl AsRef<[u8]>,
+        end: impl AsRef<[u8]>,
+    ) -> Result<()> {
+        let mut batch = WriteBatch::new();
+        batch.delete_range(begin, end);
+        self.write(options, &batch)
     }
 
-    pub fn create_snapshot(&self) -> OwnedSnapshot<'_, Self> {
-        let inner = unsafe { rocksdb_create_snapshot(self.inner) };
-        debug_assert!(!inner.is_null());
-        OwnedSnapshot { inner, db: self }
+    pub fn delete_range_cf(
+        &self,
+        options: &WriteOptions,
+        cf: &ColumnFamily,
+        begin: impl AsRef<[u8]>,
+        end: impl AsRef<[u8]>,
+    ) -> Result<()> {
+        let cf = cf.as_ptr_for(self.inner)?;
+        let (begin, end) = (begin.as_ref(), end.as_ref());
+        let _op = Op::new("delete_range_cf", begin.len(), 0);
+        Ok(ffi!(rocksdb_delete_range_cf(
+            self.inner,
+            options.inner,
+            cf,
+            begin.as_ptr() as _,
+            begin.len(),
+            end.as_ptr() as _,
+            end.len()
+        )))
     }
-}
 
-impl ReleaseSnapshot for DB {
-    fn release_snapshot(&self, snapshot: *const rocksdb_snapshot_t) {
-        unsafe { rocksdb_release_snapshot(self.inner, snapshot) }
+    // Deletes every key starting with prefix with a single range tombstone.
+    // A prefix of only 0xff bytes has no successor, every key after it has
+    // the prefix, so the range runs to the last key of the db, which is
+    // deleted on its own. An empty prefix is rejected rather than deleting
+    // everything.
+    pub fn delete_prefix(&self, options: &WriteOptions, prefix: impl AsRef<[u8]>) -> Result<()> {
+        let prefix = prefix.as_ref();
+        if prefix.is_empty() {
+            return Err(Error::invalid_argument(
+                "delete_prefix needs a non-empty prefix",
+            ));
+        }
+        if let Some(end) = prefix_successor(prefix) {
+            return self.delete_range(options, prefix, end);
+        }
+        let mut iter = self.create_iterator(default_read_options());
+        iter.seek_to_last();
+        let last = match iter.try_key()? {
+            Some(last) if last.as_ref() >= prefix => last.as_ref().to_vec(),
+            _ => return Ok(()),
+        };
+        drop(iter);
+        let mut batch = WriteBatch::new();
+        batch.delete_range(prefix, &last);
+        batch.delete(&last);
+        self.write(options, &batch)
     }
-}
 
-impl Drop for DB {
-    fn drop(&mut self) {
-        unsafe { rocksdb_close(self.inner) }
+    pub fn flush(&self, options: &FlushOptions) -> Result<()> {
+        Ok(ffi!(rocksdb_flush(self.inner, options.inner)))
     }
-}
-
-unsafe impl Send for DB {}
-
-unsafe impl Sync for DB {}
 
-#[cfg(test)]
-mod tests {
-    use crate::options::tests::DBPath;
-    use crate::snapshot::NullSnapshot;
-    use crate::{Options, ReadOptions, WriteBatch, WriteOptions, DB};
+    pub fn flush_cf(&self, options: &FlushOptions, cf: &ColumnFamily) -> Result<()> {
+        let cf = cf.as_ptr_for(self.inner)?;
+        Ok(ffi!(rocksdb_flush_cf(self.inner, options.inner, cf)))
+    }
 
-    #[test]
-    fn test_open() {
-        let mut options = Options::new();
-        options.set_create_if_missing(true);
-        options.set_error_if_exists(true);
-        let path = DBPath::new();
-        assert!(DB::open(&options, path.as_ref()).is_ok());
+    // Flushes every open column family, the first error is returned after
+    // trying them all.
+    fn flush_all(&self, options: &FlushOptions) -> Result<()> {
+        let column_families = self
+            .column_families
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        // ffi! returns on error, the closure keeps that to one family
+        let flush_cf = |raw: &RawColumnFamily| -> Result<()> {
+            Ok(ffi!(rocksdb_flush_cf(
+                self.inner,
+                options.inner,
+                raw.as_ptr()
+            )))
+        };
+        let mut result = Ok(());
+        for raw in column_families.values() {
+            result = result.and(flush_cf(raw));
+        }
+        result
     }
 
-    #[test]
-    fn test_destroy() {
-        let mut options = Options::new();
-        options.set_create_if_missing(true);
-        let path = DBPath::new();
-        assert!(DB::open(&options, path.as_ref()).is_ok());
+    pub fn latest_sequence_number(&self) -> u64 {
+        unsafe { rocksdb_get_latest_sequence_number(self.inner) }
+    }
 
-        options.set_create_if_missing(false);
-        assert!(DB::open(&options, path.as_ref()).is_ok());
+    // The C API has no FlushWAL/SyncWAL. A synced write of an empty batch
+    // syncs the WAL, including all unsynced writes before it.
+    pub fn sync_wal(&self) -> Result<()> {
+        let mut options = WriteOptions::new();
+        options.set_sync(true);
+        self.write(&options, &WriteBatch::new())
+    }
 
-        assert!(DB::destroy(&options, path.as_ref()).is_ok());
-        assert!(DB::open(&options, path.as_ref()).is_err());
+    // One place for an orderly close: flushes the memtables of all column
+    // families, syncs the WAL and optionally cancels pending background work
+    // before closing. Every step runs even if an earlier one failed, the
+    // report has all the results.
+    pub fn shutdown(self, options: &ShutdownOptions) -> ShutdownReport {
+        let start = Instant::now();
+        let flush = options.flush.then(|| {
+            let mut options = FlushOptions::new();
+            options.set_wait(true);
+            self.flush_all(&options)
+        });
+        let sync_wal = options.sync_wal.then(|| self.sync_wal());
+        if options.cancel_background_work {
+            unsafe { rocksdb_cancel_all_background_work(self.inner, true as c_uchar) };
+        }
+        drop(self);
+        ShutdownReport {
+            flush,
+            sync_wal,
+            duration: start.elapsed(),
+        }
     }
 
-    fn open_new_db(name: &str) -> DB {
-        let mut options = Options::new();
-        options.set_create_if_missing(true);
-        options.set_error_if_exists(true);
-        DB::open(&options, name).unwrap()
+    // The C API has no VerifyChecksum, so this reads the whole db with
+    // verify_checksums set, which checks every block the scan touches.
+    pub fn verify_checksums(&self) -> Result<()> {
+        let mut options = ReadOptions::new();
+        options.set_verify_checksums(true).set_fill_cache(false);
+        let mut iter = self.create_iterator(&options);
+        iter.seek_to_first();
+        while iter.valid() {
+            iter.next();
+        }
+        match iter.get_error() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
     }
 
-    #[test]
-    fn test_get_put_delete() {
-        let path = DBPath::new();
-        let db = open_new_db(path.as_ref());
+    // Sizes of the table files holding each [start, limit) range, memtables
+    // aren't included.
+    pub fn approximate_sizes(&self, ranges: &[(&[u8], &[u8])]) -> Result<Vec<u64>> {
+        let starts: Vec<_> = ranges
+            .iter()
+            .map(|r| r.0.as_ptr() as *const c_char)
+            .collect();
+        let start_lens: Vec<_> = ranges.iter().map(|r| r.0.len()).collect();
+        let limits: Vec<_> = ranges
+            .iter()
+            .map(|r| r.1.as_ptr() as *const c_char)
+            .collect();
+        let limit_lens: Vec<_> = ranges.iter().map(|r| r.1.len()).collect();
+        let mut sizes = vec![0; ranges.len()];
+        ffi!(rocksdb_approximate_sizes(
+            self.inner,
+            ranges.len() as c_int,
+            starts.as_ptr(),
+            start_lens.as_ptr(),
+            limits.as_ptr(),
+            limit_lens.as_ptr(),
+            sizes.as_mut_ptr()
+        ));
+        Ok(sizes)
+    }
 
-        let read_op = ReadOptions::new();
-        assert!(db.get(&read_op, "foo").unwrap().is_none());
+    // None if the property is unknown
+    pub fn property_value(&self, name: &str) -> Result<Option<String>> {
+        let name = property_name(name)?;
+        let value = unsafe { rocksdb_property_value(self.inner, name.as_ptr()) };
+        Ok(property_string(value))
+    }
 
-        let write_op = WriteOptions::new();
-        assert!(db.put(&write_op, "foo", "bar").is_ok());
-        assert_eq!(db.get(&read_op, "foo").unwrap().unwrap().as_ref(), b"bar");
+    pub fn property_value_cf(&self, cf: &ColumnFamily, name: &str) -> Result<Option<String>> {
+        let cf = cf.as_ptr_for(self.inner)?;
+        let name = property_name(name)?;
+        let value = unsafe { rocksdb_property_value_cf(self.inner, cf, name.as_ptr()) };
+        Ok(property_string(value))
+    }
 
-        assert!(db.delete(&write_op, "foo").is_ok());
-        assert!(db.get(&read_op, "foo").unwrap().is_none());
+    // Compacts all files overlapping [start, end], None is unbounded. Blocks
+    // until the compaction is done.
+    pub fn compact_range(&self, start: Option<&[u8]>, end: Option<&[u8]>) {
+        self.compact_range_opt(&CompactRangeOptions::new(), start, end)
+    }
+
+    pub fn compact_range_opt(
+        &self,
+        options: &CompactRangeOptions,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) {
+        let (start, start_len) = optional_key(start);
+        let (end, end_len) = optional_key(end);
+        let _op = Op::new("compact_range", 0, 0);
+        unsafe {
+            rocksdb_compact_range_opt(self.inner, options.inner, start, start_len, end, end_len)
+        }
+    }
+
+    pub fn compact_range_cf(
+        &self,
+        options: &CompactRangeOptions,
+        cf: &ColumnFamily,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Result<()> {
+        let cf = cf.as_ptr_for(self.inner)?;
+        let (start, start_len) = optional_key(start);
+        let (end, end_len) = optional_key(end);
+        let _op = Op::new("compact_range_cf", 0, 0);
+        unsafe {
+            rocksdb_compact_range_cf_opt(
+                self.inner,
+                cf,
+                options.inner,
+                start,
+                start_len,
+                end,
+                end_len,
+            )
+        }
+        Ok(())
+    }
+
+    // Logical sizes (raw key and value bytes) next to the physical ones
+    // (data, index and filter blocks) of all table files.
+    pub fn table_properties(&self) -> Result<TableProperties> {
+        match self.property_value("rocksdb.aggregated-table-properties")? {
+            Some(s) => Ok(TableProperties::parse(&s)),
+            None => Ok(TableProperties::default()),
+        }
+    }
+
+    pub fn table_properties_cf(&self, cf: &ColumnFamily) -> Result<TableProperties> {
+        match self.property_value_cf(cf, "rocksdb.aggregated-table-properties")? {
+            Some(s) => Ok(TableProperties::parse(&s)),
+            None => Ok(TableProperties::default()),
+        }
+    }
+
+    pub fn ingest_external_file(
+        &self,
+        options: &IngestExternalFileOptions,
+        paths: &[impl AsRef<Path>],
+    ) -> Result<()> {
+        let paths = paths
+            .iter()
+            .map(|path| path_to_cstring(path.as_ref()))
+            .collect::<Result<Vec<_>>>()?;
+        let paths: Vec<_> = paths.iter().map(|path| path.as_ptr()).collect();
+        Ok(ffi!(rocksdb_ingest_external_file(
+            self.inner,
+            paths.as_ptr(),
+            paths.len(),
+            options.inner
+        )))
+    }
+
+    // only mutable options can be changed on an open db, e.g. write_buffer_size
+    pub fn set_options(&self, options: &[(&str, &str)]) -> Result<()> {
+        let (keys, values) = options_to_cstrings(options)?;
+        let keys: Vec<_> = keys.iter().map(|k| k.as_ptr()).collect();
+        let values: Vec<_> = values.iter().map(|v| v.as_ptr()).collect();
+        Ok(ffi!(rocksdb_set_options(
+            self.inner,
+            options.len() as _,
+            keys.as_ptr(),
+            values.as_ptr()
+        )))
+    }
+
+    pub fn set_options_cf(&self, cf: &ColumnFamily, options: &[(&str, &str)]) -> Result<()> {
+        let cf = cf.as_ptr_for(self.inner)?;
+        let (keys, values) = options_to_cstrings(options)?;
+        let keys: Vec<_> = keys.iter().map(|k| k.as_ptr()).collect();
+        let values: Vec<_> = values.iter().map(|v| v.as_ptr()).collect();
+        Ok(ffi!(rocksdb_set_options_cf(
+            self.inner,
+            cf,
+            options.len() as _,
+            keys.as_ptr(),
+            values.as_ptr()
+        )))
+    }
+
+    pub fn create_snapshot(&self) -> OwnedSnapshot<'_, Self> {
+        let inner = unsafe { rocksdb_create_snapshot(self.inner) };
+        debug_assert!(!inner.is_null());
+        OwnedSnapshot { inner, db: self }
+    }
+
+    pub fn create_shared_snapshot(self: &Arc<Self>) -> SharedSnapshot<Self> {
+        let inner = unsafe { rocksdb_create_snapshot(self.inner) };
+        debug_assert!(!inner.is_null());
+        SharedSnapshot {
+            inner,
+            db: self.clone(),
+        }
+    }
+
+    pub fn read_view(&self) -> SnapshotView<'_> {
+        let snapshot = self.create_snapshot();
+        let options = ReadOptions::new();
+        unsafe { rocksdb_readoptions_set_snapshot(options.inner, snapshot.inner) };
+        SnapshotView { options, snapshot }
+    }
+
+    pub fn create_iterator_default(&self) -> crate::Iterator<'_> {
+        self.create_iterator(default_read_options())
+    }
+
+    pub fn get_default(&self, key: impl AsRef<[u8]>) -> Result<Option<Bytes>> {
+        self.get(default_read_options(), key)
+    }
+
+    pub fn put_default(&self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) -> Result<()> {
+        self.put(default_write_options(), key, value)
+    }
+
+    pub fn delete_default(&self, key: impl AsRef<[u8]>) -> Result<()> {
+        self.delete(default_write_options(), key)
+    }
+
+    pub fn merge_default(&self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) -> Result<()> {
+        self.merge(default_write_options(), key, value)
+    }
+}
+
+pub struct SnapshotView<'a> {
+    options: ReadOptions<'a>,
+    snapshot: OwnedSnapshot<'a, DB>,
+}
+
+impl<'a> SnapshotView<'a> {
+    pub fn get(&self, key: impl AsRef<[u8]>) -> Result<Option<Bytes>> {
+        self.snapshot.db.get(&self.options, key)
+    }
+
+    pub fn multi_get(&self, keys: &[impl AsRef<[u8]>]) -> Vec<Result<Option<Bytes>>> {
+        self.snapshot.db.multi_get(&self.options, keys)
+    }
+
+    pub fn create_iterator(&self) -> crate::Iterator<'_> {
+        self.snapshot.db.create_iterator(&self.options)
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        self.snapshot.db.iter(&self.options)
+    }
+
+    pub fn iter_rev(&self) -> Iter<'_> {
+        self.snapshot.db.iter_rev(&self.options)
+    }
+
+    pub fn range(&self, range: impl KeyRange) -> Iter<'_> {
+        let options = ReadOptions::new();
+        unsafe { rocksdb_readoptions_set_snapshot(options.inner, self.snapshot.inner) };
+        self.snapshot.db.range(options, range)
+    }
+}
+
+impl ReleaseSnapshot for DB {
+    fn release_snapshot(&self, snapshot: *const rocksdb_snapshot_t) {
+        unsafe { rocksdb_release_snapshot(self.inner, snapshot) }
+    }
+}
+
+impl Drop for DB {
+    fn drop(&mut self) {
+        // handles must be destroyed before the db is closed
+        self.column_families
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+        unsafe { rocksdb_close(self.inner) }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ShutdownOptions {
+    flush: bool,
+    sync_wal: bool,
+    cancel_background_work: bool,
+}
+
+impl ShutdownOptions {
+    pub fn new() -> Self {
+        Self {
+            flush: true,
+            sync_wal: true,
+            cancel_background_work: false,
+        }
+    }
+
+    pub fn set_flush(&mut self, flush: bool) -> &mut Self {
+        self.flush = flush;
+        self
+    }
+
+    pub fn set_sync_wal(&mut self, sync: bool) -> &mut Self {
+        self.sync_wal = sync;
+        self
+    }
+
+    // Drops compactions and flushes that haven't started yet instead of
+    // running them; the ones already running are waited for either way.
+    pub fn set_cancel_background_work(&mut self, cancel: bool) -> &mut Self {
+        self.cancel_background_work = cancel;
+        self
+    }
+}
+
+impl Default for ShutdownOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// None for the steps that were skipped.
+#[derive(Debug)]
+pub struct ShutdownReport {
+    pub flush: Option<Result<()>>,
+    pub sync_wal: Option<Result<()>>,
+    pub duration: Duration,
+}
+
+impl ShutdownReport {
+    // the first error, if any step failed
+    pub fn result(&self) -> Result<()> {
+        for step in [&self.flush, &self.sync_wal].into_iter().flatten() {
+            step.clone()?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct RepairReport {
+    pub open_error: Error,
+}
+
+fn is_repairable(error: &Error) -> bool {
+    match error.kind() {
+        ErrorKind::Corruption => true,
+        ErrorKind::IOError => error.subcode() == SubCode::PathNotFound,
+        _ => false,
+    }
+}
+
+fn options_to_cstrings(options: &[(&str, &str)]) -> Result<(Vec<CString>, Vec<CString>)> {
+    let cstring = |s: &str| {
+        CString::new(s).map_err(|_| Error::invalid_argument("option contains an interior nul byte"))
+    };
+    let mut keys = Vec::with_capacity(options.len());
+    let mut values = Vec::with_capacity(options.len());
+    for (key, value) in options {
+        keys.push(cstring(key)?);
+        values.push(cstring(value)?);
+    }
+    Ok((keys, values))
+}
+
+const DEFAULT_CF: &str = "default";
+
+pub(crate) type ColumnFamilies = HashMap<String, Arc<RawColumnFamily>>;
+
+// Opens the db with its default column family and the listed ones, so that
+// the default handle is always available.
+pub(crate) fn open_column_families(
+    options: &Options,
+    path: &Path,
+    column_families: &[(&str, &Options)],
+) -> Result<(*mut rocksdb_t, ColumnFamilies)> {
+    let mut column_families = column_families.to_vec();
+    if !column_families.iter().any(|(name, _)| *name == DEFAULT_CF) {
+        column_families.insert(0, (DEFAULT_CF, options));
+    }
+    let names = column_families
+        .iter()
+        .map(|(name, _)| cf_name_to_cstring(name))
+        .collect::<Result<Vec<_>>>()?;
+    let name_ptrs: Vec<_> = names.iter().map(|name| name.as_ptr()).collect();
+    let cf_options: Vec<_> = column_families
+        .iter()
+        .map(|(_, options)| options.inner as *const _)
+        .collect();
+    let mut handles = vec![null_mut(); column_families.len()];
+
+    let path = path_to_cstring(path)?;
+    let inner = ffi!(rocksdb_open_column_families(
+        options.inner,
+        path.as_ptr(),
+        column_families.len() as c_int,
+        name_ptrs.as_ptr(),
+        cf_options.as_ptr(),
+        handles.as_mut_ptr()
+    ));
+    let column_families = column_families
+        .iter()
+        .zip(handles)
+        .map(|((name, _), handle)| {
+            (
+                name.to_string(),
+                RawColumnFamily::new(handle, inner, name.to_string()),
+            )
+        })
+        .collect();
+    Ok((inner, column_families))
+}
+
+fn property_name(name: &str) -> Result<CString> {
+    CString::new(name)
+        .map_err(|_| Error::invalid_argument("property name contains an interior nul byte"))
+}
+
+// takes ownership of the value, None if it is null
+fn property_string(value: *mut c_char) -> Option<String> {
+    if value.is_null() {
+        return None;
+    }
+    let s = unsafe { CStr::from_ptr(value) }
+        .to_string_lossy()
+        .into_owned();
+    free(value);
+    Some(s)
+}
+
+// a null pointer for None
+fn optional_key(key: Option<&[u8]>) -> (*const c_char, usize) {
+    match key {
+        Some(key) => (key.as_ptr() as _, key.len()),
+        None => (null(), 0),
+    }
+}
+
+fn cf_name_to_cstring(name: &str) -> Result<CString> {
+    CString::new(name)
+        .map_err(|_| Error::invalid_argument("column family name contains an interior nul byte"))
+}
+
+fn multi_get_results(
+    values_list: Vec<*mut c_char>,
+    values_list_sizes: Vec<usize>,
+    errs: Vec<*mut c_char>,
+) -> Vec<Result<Option<Bytes>>> {
+    let mut ret = Vec::with_capacity(errs.len());
+    for i in 0..errs.len() {
+        let err = errs[i];
+        let v = if err.is_null() {
+            let value = values_list[i];
+            if !value.is_null() {
+                Ok(Some(Bytes::new(value, values_list_sizes[i])))
+            } else {
+                Ok(None)
+            }
+        } else {
+            Err(Error::new(err))
+        };
+        ret.push(v);
+    }
+    ret
+}
+
+unsafe impl Send for DB {}
+
+unsafe impl Sync for DB {}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::fs;
+    use std::ops::Bound;
+    use std::path::Path;
+    use std::sync::Arc;
+    use std::thread;
+
+    use crate::options::tests::DBPath;
+    use crate::prefix_successor;
+    use crate::snapshot::NullSnapshot;
+    use crate::{
+        BottommostLevelCompaction, CompactRangeOptions, Direction, ErrorKind, FlushOptions,
+        IngestExternalFileOptions, Iter, Options, ReadOptions, SharedIterator, ShutdownOptions,
+        SstFileWriter, WriteBatch, WriteBatchWithIndex, WriteOptions, DB,
+    };
+
+    #[test]
+    fn test_open() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        options.set_error_if_exists(true);
+        let path = DBPath::new();
+        assert!(DB::open(&options, path.as_ref()).is_ok());
+    }
+
+    #[test]
+    fn test_set_options() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+
+        assert!(db
+            .set_options(&[
+                ("write_buffer_size", "8388608"),
+                ("disable_auto_compactions", "true"),
+            ])
+            .is_ok());
+        assert!(db.set_options(&[("no_such_option", "1")]).is_err());
+        // not a mutable option
+        assert!(db.set_options(&[("create_if_missing", "false")]).is_err());
+    }
+
+    #[test]
+    fn test_set_options_cf() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+        let cf = db.create_cf("a", &Options::new()).unwrap();
+        db.set_options_cf(&cf, &[("disable_auto_compactions", "true")])
+            .unwrap();
+        assert!(db.set_options_cf(&cf, &[("no_such_option", "1")]).is_err());
+
+        // only the family's section of the latest OPTIONS file has the change
+        let options_file = fs::read_dir(path.as_ref())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                path.file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .starts_with("OPTIONS-")
+            })
+            .max()
+            .unwrap();
+        let contents = fs::read_to_string(options_file).unwrap();
+        let section = |name: &str| {
+            let start = contents.find(&format!("[CFOptions \"{}\"]", name)).unwrap();
+            let end = contents[start + 1..]
+                .find("[CFOptions")
+                .map_or(contents.len(), |end| start + 1 + end);
+            contents[start..end].to_string()
+        };
+        assert!(section("a").contains("disable_auto_compactions=true"));
+        assert!(section("default").contains("disable_auto_compactions=false"));
+    }
+
+    #[test]
+    fn test_verify_checksums() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        for i in 0..100u32 {
+            db.put_default(i.to_be_bytes(), i.to_le_bytes()).unwrap();
+        }
+        db.flush(&FlushOptions::new()).unwrap();
+        assert!(db.verify_checksums().is_ok());
+    }
+
+    #[test]
+    fn test_ingest_external_file_missing() {
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_allow_ingest_behind(true);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+
+        let mut ingest_options = IngestExternalFileOptions::new();
+        ingest_options.set_ingest_behind(true);
+        let missing = format!("{}.sst", path.as_ref());
+        assert!(db
+            .ingest_external_file(&ingest_options, &[missing])
+            .is_err());
+    }
+
+    #[test]
+    fn test_ingest_behind() {
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_allow_ingest_behind(true);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        db.put_default("a", "new").unwrap();
+        db.flush(&FlushOptions::new()).unwrap();
+        db.put_default("b", "new").unwrap();
+
+        let file = format!("{}/old.sst", path.as_ref());
+        let mut writer = SstFileWriter::new(&options);
+        writer.open(&file).unwrap();
+        for key in ["a", "b", "c"] {
+            writer.put(key, "old").unwrap();
+        }
+        writer.finish().unwrap();
+
+        let mut ingest_options = IngestExternalFileOptions::new();
+        ingest_options.set_ingest_behind(true);
+        db.ingest_external_file(&ingest_options, &[file]).unwrap();
+        // existing keys take precedence over the ingested ones
+        assert_eq!(db.get_default("a").unwrap().unwrap(), b"new");
+        assert_eq!(db.get_default("b").unwrap().unwrap(), b"new");
+        assert_eq!(db.get_default("c").unwrap().unwrap(), b"old");
+    }
+
+    #[test]
+    fn test_open_or_repair() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        {
+            let (db, report) = DB::open_or_repair(&options, path.as_ref()).unwrap();
+            assert!(report.is_none());
+            db.put_default("foo", "bar").unwrap();
+            db.flush(&FlushOptions::new()).unwrap();
+        }
+
+        fs::write(Path::new(path.as_ref()).join("CURRENT"), "garbage").unwrap();
+        assert!(DB::open(&options, path.as_ref()).is_err());
+
+        let (db, report) = DB::open_or_repair(&options, path.as_ref()).unwrap();
+        assert_eq!(report.unwrap().open_error.kind(), ErrorKind::Corruption);
+        assert_eq!(db.get_default("foo").unwrap().unwrap(), b"bar");
+    }
+
+    #[test]
+    fn test_destroy() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        assert!(DB::open(&options, path.as_ref()).is_ok());
+
+        options.set_create_if_missing(false);
+        assert!(DB::open(&options, path.as_ref()).is_ok());
+
+        assert!(DB::destroy(&options, path.as_ref()).is_ok());
+        assert!(DB::open(&options, path.as_ref()).is_err());
+    }
+
+    #[test]
+    fn test_open_twice() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let e = DB::open(&options, path.as_ref()).err().unwrap();
+        assert_eq!(e.kind(), ErrorKind::Busy);
+        drop(db);
+        assert!(DB::open(&options, path.as_ref()).is_ok());
+    }
+
+    #[test]
+    fn test_open_race() {
+        let path = DBPath::new();
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let results: Vec<_> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| scope.spawn(|| DB::open(&options, path.as_ref())))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 1);
+        for e in results.iter().filter_map(|r| r.as_ref().err()) {
+            assert_eq!(e.kind(), ErrorKind::Busy);
+        }
+        drop(results);
+
+        // a failed open doesn't leave the path registered
+        let missing = DBPath::new();
+        assert!(DB::open(&Options::new(), missing.as_ref()).is_err());
+        assert!(DB::open(&options, missing.as_ref()).is_ok());
+    }
+
+    #[test]
+    fn test_open_shared() {
+        let path = DBPath::new();
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let db1 = DB::open_shared(&options, path.as_ref()).unwrap();
+        let db2 = DB::open_shared(&options, path.as_ref()).unwrap();
+        assert!(Arc::ptr_eq(&db1, &db2));
+        let e = DB::open(&options, path.as_ref()).err().unwrap();
+        assert_eq!(e.kind(), ErrorKind::Busy);
+        drop(db1);
+        drop(db2);
+        assert!(DB::open(&options, path.as_ref()).is_ok());
+    }
+
+    #[test]
+    fn test_table_properties() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+        assert_eq!(db.property_value("rocksdb.no-such-property").unwrap(), None);
+        assert_eq!(db.table_properties().unwrap().num_entries, 0);
+
+        for i in 0..10 {
+            db.put_default(format!("key{}", i), "value").unwrap();
+        }
+        db.delete_default("other").unwrap();
+        db.flush(&FlushOptions::new()).unwrap();
+        let props = db.table_properties().unwrap();
+        assert_eq!(props.num_entries, 11);
+        assert_eq!(props.num_deletions, 1);
+        assert_eq!(props.raw_value_size, 50);
+        assert!(props.data_size > 0);
+
+        let cf = db.create_cf("a", &Options::new()).unwrap();
+        assert_eq!(db.table_properties_cf(&cf).unwrap().num_entries, 0);
+        let write_op = WriteOptions::new();
+        db.put_cf(&write_op, &cf, "key", "value").unwrap();
+        db.flush_cf(&FlushOptions::new(), &cf).unwrap();
+        assert_eq!(db.table_properties_cf(&cf).unwrap().num_entries, 1);
+        assert!(db
+            .property_value_cf(&cf, "rocksdb.num-files-at-level0")
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_shutdown() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+        let mut write_options = WriteOptions::new();
+        write_options.set_disable_wal(true);
+        db.put(&write_options, "key", "value").unwrap();
+
+        let mut options = ShutdownOptions::new();
+        options.set_cancel_background_work(true);
+        let report = db.shutdown(&options);
+        assert!(report.flush.is_some());
+        assert!(report.result().is_ok());
+
+        // the write skipped the WAL, only the flush saved it
+        let db = DB::open(&Options::new(), path.as_ref()).unwrap();
+        assert_eq!(db.get_default("key").unwrap().unwrap(), b"value");
+    }
+
+    #[test]
+    fn test_shutdown_flushes_column_families() {
+        let path = DBPath::new();
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_create_missing_column_families(true);
+        let cf_options = Options::new();
+        let db = DB::open_cf(&options, path.as_ref(), &[("a", &cf_options)]).unwrap();
+        let mut write_options = WriteOptions::new();
+        write_options.set_disable_wal(true);
+        let cf = db.cf_handle("a").unwrap();
+        db.put_cf(&write_options, &cf, "key", "value").unwrap();
+        drop(cf);
+
+        let report = db.shutdown(&ShutdownOptions::new());
+        assert!(report.result().is_ok());
+
+        let db = DB::open_cf(&options, path.as_ref(), &[("a", &cf_options)]).unwrap();
+        let cf = db.cf_handle("a").unwrap();
+        assert_eq!(
+            db.get_cf(&ReadOptions::new(), &cf, "key").unwrap().unwrap(),
+            b"value"
+        );
+    }
+
+    #[test]
+    fn test_prefix_successor() {
+        assert_eq!(prefix_successor(b"ab"), Some(b"ac".to_vec()));
+        assert_eq!(prefix_successor(b"a\xff"), Some(b"b".to_vec()));
+        assert_eq!(prefix_successor(b"\xff\xff"), None);
+    }
+
+    #[test]
+    fn test_delete_prefix() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+        let keys: [&[u8]; 8] = [
+            b"a",
+            b"ab",
+            b"ab\xff",
+            b"abc",
+            b"ac",
+            b"\xfe",
+            b"\xff",
+            b"\xff\xff",
+        ];
+        for key in keys {
+            db.put_default(key, "").unwrap();
+        }
+        db.delete_prefix(&WriteOptions::new(), "ab").unwrap();
+        db.delete_prefix(&WriteOptions::new(), b"\xff").unwrap();
+        let keys: Vec<_> = db
+            .iter(&ReadOptions::new())
+            .map(|item| item.unwrap().0)
+            .collect();
+        assert_eq!(keys, [&b"a"[..], b"ac", b"\xfe"].map(Box::from));
+
+        // no key has the prefix
+        db.delete_prefix(&WriteOptions::new(), b"\xff").unwrap();
+        assert_eq!(
+            db.delete_prefix(&WriteOptions::new(), "")
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidArgument
+        );
+        assert_eq!(db.iter(&ReadOptions::new()).count(), 3);
+    }
+
+    #[test]
+    fn test_open_nul_path() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        assert!(DB::open(&options, "/tmp/rocksdb\0test").is_err());
+        assert!(DB::destroy(&options, "/tmp/rocksdb\0test").is_err());
+        assert!(DB::repair(&options, "/tmp/rocksdb\0test").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_open_non_utf8_path() {
+        use std::ffi::OsStr;
+        use std::fs::remove_dir_all;
+        use std::os::unix::ffi::OsStrExt;
+
+        let mut name = DBPath::new().as_ref().as_bytes().to_vec();
+        name.push(0xff);
+        let path = OsStr::from_bytes(&name);
+
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        assert!(DB::open(&options, path).is_ok());
+        assert!(DB::destroy(&options, path).is_ok());
+        let _ = remove_dir_all(path);
+    }
+
+    fn open_new_db(name: &str) -> DB {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        options.set_error_if_exists(true);
+        DB::open(&options, name).unwrap()
+    }
+
+    #[test]
+    fn test_column_families() {
+        let path = DBPath::new();
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        {
+            let db = DB::open(&options, path.as_ref()).unwrap();
+            assert!(db.cf_handle("default").is_some());
+            let cf = db.create_cf("a", &Options::new()).unwrap();
+            assert_eq!(cf.name(), "a");
+            assert!(db.cf_handle("a").is_some());
+            assert!(db.cf_handle("b").is_none());
+            assert_eq!(
+                db.create_cf("a", &Options::new()).err().unwrap().kind(),
+                ErrorKind::InvalidArgument
+            );
+        }
+        assert_eq!(
+            DB::list_cf(&options, path.as_ref()).unwrap(),
+            ["default", "a"]
+        );
+        // all families must be opened
+        assert!(DB::open_cf(&options, path.as_ref(), &[]).is_err());
+
+        options.set_create_missing_column_families(true);
+        let cf_options = Options::new();
+        let db = DB::open_cf(
+            &options,
+            path.as_ref(),
+            &[("a", &cf_options), ("b", &cf_options)],
+        )
+        .unwrap();
+        assert!(db.cf_handle("default").is_some());
+        let a = db.cf_handle("a").unwrap();
+        assert!(db.cf_handle("b").is_some());
+
+        db.drop_cf("a").unwrap();
+        assert!(db.cf_handle("a").is_none());
+        assert_eq!(a.name(), "a");
+        assert_eq!(
+            db.drop_cf("a").unwrap_err().kind(),
+            ErrorKind::InvalidArgument
+        );
+        drop(a);
+        drop(db);
+        assert_eq!(
+            DB::list_cf(&options, path.as_ref()).unwrap(),
+            ["default", "b"]
+        );
+    }
+
+    #[test]
+    fn test_column_family_data() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+        let a = db.create_cf("a", &Options::new()).unwrap();
+        let b = db.create_cf("b", &Options::new()).unwrap();
+
+        let read_op = ReadOptions::new();
+        let write_op = WriteOptions::new();
+        db.put_cf(&write_op, &a, "k", "a").unwrap();
+        db.put_cf(&write_op, &b, "k", "b").unwrap();
+        db.put_cf(&write_op, &b, "l", "b").unwrap();
+        assert!(db.get(&read_op, "k").unwrap().is_none());
+        assert_eq!(db.get_cf(&read_op, &a, "k").unwrap().unwrap(), b"a");
+
+        let values = db.multi_get_cf(&read_op, &[(&a, "k"), (&b, "k"), (&a, "l")]);
+        assert_eq!(values[0].as_ref().unwrap().as_ref().unwrap(), b"a");
+        assert_eq!(values[1].as_ref().unwrap().as_ref().unwrap(), b"b");
+        assert!(values[2].as_ref().unwrap().is_none());
+
+        let mut iter = db.create_iterator_cf(&read_op, &b).unwrap();
+        iter.seek_to_first();
+        let keys: Vec<_> = iter.into_iter().map(|item| item.unwrap().0).collect();
+        assert_eq!(keys, [&b"k"[..], b"l"].map(Box::from));
+
+        let mut iters = db.create_iterators(&read_op, &[&a, &b]).unwrap();
+        db.delete_cf(&write_op, &b, "k").unwrap();
+        assert!(db.get_cf(&read_op, &b, "k").unwrap().is_none());
+        // the iterators still see the deleted key
+        for iter in &mut iters {
+            iter.seek_to_first();
+        }
+        let counts: Vec<_> = iters
+            .into_iter()
+            .map(|iter| iter.into_iter().count())
+            .collect();
+        assert_eq!(counts, [1, 2]);
+    }
+
+    #[test]
+    fn test_column_family_of_another_db() {
+        let (path1, path2) = (DBPath::new(), DBPath::new());
+        let db1 = open_new_db(path1.as_ref());
+        let db2 = open_new_db(path2.as_ref());
+        let cf = db1.create_cf("a", &Options::new()).unwrap();
+        let default = db1.cf_handle("default").unwrap();
+
+        let write_op = WriteOptions::new();
+        let read_op = ReadOptions::new();
+        db1.put_cf(&write_op, &default, "k", "v").unwrap();
+        assert_eq!(db1.get_default("k").unwrap().unwrap(), b"v");
+        assert_eq!(
+            db2.put_cf(&write_op, &cf, "k", "v").unwrap_err().kind(),
+            ErrorKind::InvalidArgument
+        );
+        assert_eq!(
+            db2.get_cf(&read_op, &default, "k").unwrap_err().kind(),
+            ErrorKind::InvalidArgument
+        );
+        assert!(db2.create_iterator_cf(&read_op, &cf).is_err());
+        assert!(db2.create_iterators(&read_op, &[&cf]).is_err());
+        let values = db2.multi_get_cf(&read_op, &[(&default, "k"), (&cf, "k")]);
+        assert!(values.iter().all(|value| value.is_err()));
+        assert!(db2.delete_cf(&write_op, &cf, "k").is_err());
+        assert!(db2.delete_range_cf(&write_op, &cf, "a", "b").is_err());
+        assert!(db2
+            .compact_range_cf(&CompactRangeOptions::new(), &cf, None, None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_compact_range() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+        let write_op = WriteOptions::new();
+        for i in 0..100 {
+            db.put(&write_op, format!("key{:03}", i), "value").unwrap();
+        }
+        db.flush(&FlushOptions::new()).unwrap();
+        for i in 0..100 {
+            db.delete(&write_op, format!("key{:03}", i)).unwrap();
+        }
+        db.flush(&FlushOptions::new()).unwrap();
+        assert!(db.table_properties().unwrap().num_entries > 0);
+
+        db.compact_range(Some(b"key000"), None);
+        assert_eq!(db.table_properties().unwrap().num_entries, 0);
+
+        let mut options = CompactRangeOptions::new();
+        options
+            .set_change_level(true)
+            .set_bottommost_level_compaction(BottommostLevelCompaction::Force);
+        db.compact_range_opt(&options, None, None);
+        let cf = db.create_cf("a", &Options::new()).unwrap();
+        db.compact_range_cf(&options, &cf, None, Some(b"z"))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_delete_range() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+        let write_op = WriteOptions::new();
+        let read_op = ReadOptions::new();
+        let cf = db.create_cf("a", &Options::new()).unwrap();
+        for key in ["a", "b", "c", "d"] {
+            db.put(&write_op, key, "").unwrap();
+            db.put_cf(&write_op, &cf, key, "").unwrap();
+        }
+
+        db.delete_range(&write_op, "b", "d").unwrap();
+        let keys: Vec<_> = db.iter(&read_op).map(|item| item.unwrap().0).collect();
+        assert_eq!(keys, [&b"a"[..], b"d"].map(Box::from));
+
+        db.delete_range_cf(&write_op, &cf, "a", "c").unwrap();
+        assert!(db.get_cf(&read_op, &cf, "b").unwrap().is_none());
+        assert!(db.get_cf(&read_op, &cf, "c").unwrap().is_some());
+
+        let mut batch = WriteBatch::new();
+        batch.delete_range("a", "z");
+        db.write(&write_op, &batch).unwrap();
+        assert_eq!(db.iter(&read_op).count(), 0);
+    }
+
+    #[test]
+    fn test_get_put_delete() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+
+        let read_op = ReadOptions::new();
+        assert!(db.get(&read_op, "foo").unwrap().is_none());
+
+        let write_op = WriteOptions::new();
+        assert!(db.put(&write_op, "foo", "bar").is_ok());
+        assert_eq!(db.get(&read_op, "foo").unwrap().unwrap().as_ref(), b"bar");
+
+        assert!(db.delete(&write_op, "foo").is_ok());
+        assert!(db.get(&read_op, "foo").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_bytes() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+
+        let write_op = WriteOptions::new();
+        db.put(&write_op, "foo", "bar").unwrap();
+        db.put(&write_op, "bar", "baz").unwrap();
+
+        let read_op = ReadOptions::new();
+        let foo = db.get(&read_op, "foo").unwrap().unwrap();
+        let bar = db.get(&read_op, "bar").unwrap().unwrap();
+        assert_eq!(foo, "bar");
+        assert_eq!(foo, b"bar");
+        assert_eq!(foo.len(), 3);
+        assert!(foo < bar);
+        assert_ne!(foo, bar);
+
+        let mut set = HashSet::new();
+        set.insert(foo);
+        assert!(set.contains(b"bar".as_ref()));
+        assert!(!set.contains(b"baz".as_ref()));
+
+        let bar: Vec<u8> = bar.into();
+        assert_eq!(bar, b"baz");
+    }
+
+    #[test]
+    fn test_get_pinned() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+
+        let read_op = ReadOptions::new();
+        assert!(db.get_pinned(&read_op, "foo").unwrap().is_none());
+
+        let write_op = WriteOptions::new();
+        db.put(&write_op, "foo", "bar").unwrap();
+        assert_eq!(
+            db.get_pinned(&read_op, "foo").unwrap().unwrap().as_ref(),
+            b"bar"
+        );
+    }
+
+    #[test]
+    fn test_default_options() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+
+        assert!(db.get_default("foo").unwrap().is_none());
+        assert!(db.put_default("foo", "bar").is_ok());
+        assert_eq!(db.get_default("foo").unwrap().unwrap().as_ref(), b"bar");
+
+        let mut iter = db.create_iterator_default();
+        iter.seek_to_first();
+        assert!(iter.valid());
+        unsafe { assert_eq!(iter.key().as_ref(), b"foo") };
+
+        assert!(db.delete_default("foo").is_ok());
+        assert!(db.get_default("foo").unwrap().is_none());
     }
 
     #[test]
@@ -236,6 +1727,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_try_multi_get() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+
+        let write_op = WriteOptions::new();
+        db.put(&write_op, "foo", "bar").unwrap();
+
+        let read_op = ReadOptions::new();
+        let values = db.try_multi_get(&read_op, &["foo", "bar"]).unwrap();
+        assert_eq!(values[0].as_ref().unwrap().as_ref(), b"bar");
+        assert!(values[1].is_none());
+    }
+
     #[test]
     fn test_write_batch() {
         let path = DBPath::new();
@@ -274,6 +1779,89 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_write_batch_with_index() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+
+        let write_op = WriteOptions::new();
+        db.put(&write_op, "foo", "bar").unwrap();
+        db.put(&write_op, "bar", "baz").unwrap();
+
+        let mut wb = WriteBatchWithIndex::new(0, true);
+        wb.put("foo", "qux");
+        wb.delete("bar");
+        assert_eq!(wb.count(), 2);
+
+        let options = Options::new();
+        assert_eq!(
+            wb.get_from_batch(&options, "foo")
+                .unwrap()
+                .unwrap()
+                .as_ref(),
+            b"qux"
+        );
+        assert!(wb.get_from_batch(&options, "baz").unwrap().is_none());
+
+        db.put(&write_op, "baz", "quux").unwrap();
+        let read_op = ReadOptions::new();
+        assert_eq!(
+            wb.get_from_batch_and_db(&db, &read_op, "foo")
+                .unwrap()
+                .unwrap()
+                .as_ref(),
+            b"qux"
+        );
+        assert!(wb
+            .get_from_batch_and_db(&db, &read_op, "bar")
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            wb.get_from_batch_and_db(&db, &read_op, "baz")
+                .unwrap()
+                .unwrap()
+                .as_ref(),
+            b"quux"
+        );
+
+        assert!(db.write_with_index(&write_op, &wb).is_ok());
+        assert_eq!(db.get(&read_op, "foo").unwrap().unwrap().as_ref(), b"qux");
+        assert!(db.get(&read_op, "bar").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_write_batch_with_index_iterator() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+
+        let write_op = WriteOptions::new();
+        db.put(&write_op, "foo1", "bar1").unwrap();
+        db.put(&write_op, "foo3", "bar3").unwrap();
+
+        let mut wb = WriteBatchWithIndex::new(0, true);
+        wb.put("foo2", "bar2");
+        wb.delete("foo3");
+
+        let read_op = ReadOptions::new();
+        let mut iter = wb.create_iterator_with_base(db.create_iterator(&read_op));
+        iter.seek_to_first();
+        assert!(iter.valid());
+        unsafe {
+            assert_eq!(iter.key().as_ref(), b"foo1");
+            assert_eq!(iter.value().as_ref(), b"bar1");
+        }
+
+        iter.next();
+        assert!(iter.valid());
+        unsafe {
+            assert_eq!(iter.key().as_ref(), b"foo2");
+            assert_eq!(iter.value().as_ref(), b"bar2");
+        }
+
+        iter.next();
+        assert!(!iter.valid());
+    }
+
     #[test]
     fn test_snapshot() {
         let path = DBPath::new();
@@ -293,6 +1881,233 @@ mod tests {
         assert_eq!(db.get(&read_op, "foo").unwrap().unwrap().as_ref(), b"bar");
     }
 
+    #[test]
+    fn test_shared_snapshot() {
+        let path = DBPath::new();
+        let db = Arc::new(open_new_db(path.as_ref()));
+
+        let write_op = WriteOptions::new();
+        db.put(&write_op, "foo", "bar").unwrap();
+        let sp = thread::spawn({
+            let db = db.clone();
+            move || db.create_shared_snapshot()
+        })
+        .join()
+        .unwrap();
+        db.put(&write_op, "foo", "baz").unwrap();
+
+        let mut read_op = ReadOptions::new();
+        read_op.set_snapshot(&sp);
+        assert_eq!(db.get(&read_op, "foo").unwrap().unwrap().as_ref(), b"bar");
+    }
+
+    #[test]
+    fn test_shared_iterator() {
+        let path = DBPath::new();
+        let db = Arc::new(open_new_db(path.as_ref()));
+
+        let write_op = WriteOptions::new();
+        db.put(&write_op, "foo", "bar").unwrap();
+
+        let mut iter = db.create_shared_iterator(ReadOptions::new());
+        drop(db);
+
+        let key = thread::spawn(move || {
+            iter.seek_to_first();
+            assert!(iter.valid());
+            unsafe { iter.key().as_ref().to_vec() }
+        })
+        .join()
+        .unwrap();
+        assert_eq!(key, b"foo");
+    }
+
+    #[test]
+    fn test_shared_iterator_bounds() {
+        let path = DBPath::new();
+        let db = Arc::new(open_new_db(path.as_ref()));
+        for key in ["a", "b", "c"] {
+            db.put_default(key, "").unwrap();
+        }
+
+        // the options and their bounds go out of scope with the function
+        fn bounded(db: &Arc<DB>) -> SharedIterator<DB> {
+            let mut options = ReadOptions::new();
+            options.set_iterate_upper_bound("c");
+            db.create_shared_iterator(options)
+        }
+        let mut iter = bounded(&db);
+        drop(db);
+
+        let mut keys = Vec::new();
+        iter.seek_to_first();
+        while let Some((key, _)) = iter.item() {
+            keys.push(key.as_ref().to_vec());
+            iter.next();
+        }
+        assert_eq!(keys, [b"a", b"b"]);
+    }
+
+    #[test]
+    fn test_read_view() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+
+        let write_op = WriteOptions::new();
+        db.put(&write_op, "foo", "bar").unwrap();
+
+        let view = db.read_view();
+        db.put(&write_op, "foo", "baz").unwrap();
+        db.put(&write_op, "bar", "qux").unwrap();
+
+        assert_eq!(view.get("foo").unwrap().unwrap().as_ref(), b"bar");
+        let values = view.multi_get(&["foo", "bar"]);
+        assert_eq!(
+            values[0].as_ref().unwrap().as_ref().unwrap().as_ref(),
+            b"bar"
+        );
+        assert!(values[1].as_ref().unwrap().is_none());
+
+        let mut iter = view.create_iterator();
+        iter.seek_to_first();
+        assert!(iter.valid());
+        unsafe { assert_eq!(iter.key().as_ref(), b"foo") };
+        iter.next();
+        assert!(!iter.valid());
+    }
+
+    #[test]
+    fn test_iterator_item() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+
+        let write_op = WriteOptions::new();
+        db.put(&write_op, "foo", "bar").unwrap();
+
+        let read_op = ReadOptions::new();
+        let mut iter = db.create_iterator(&read_op);
+        assert!(iter.item().is_none());
+        assert!(iter.try_key().unwrap().is_none());
+
+        iter.seek_to_first();
+        let (key, value) = iter.item().unwrap();
+        assert_eq!(key.as_ref(), b"foo");
+        assert_eq!(value.as_ref(), b"bar");
+        assert_eq!(iter.try_key().unwrap().unwrap().as_ref(), b"foo");
+        assert_eq!(iter.try_value().unwrap().unwrap().as_ref(), b"bar");
+
+        iter.next();
+        assert!(iter.item().is_none());
+        assert!(iter.try_value().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_iter() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+
+        let write_op = WriteOptions::new();
+        db.put(&write_op, "foo1", "bar1").unwrap();
+        db.put(&write_op, "foo2", "bar2").unwrap();
+
+        let read_op = ReadOptions::new();
+        let items: Vec<_> = db.iter(&read_op).map(|item| item.unwrap()).collect();
+        assert_eq!(items.len(), 2);
+        assert_eq!(&*items[0].0, b"foo1");
+        assert_eq!(&*items[0].1, b"bar1");
+        assert_eq!(&*items[1].0, b"foo2");
+        assert_eq!(&*items[1].1, b"bar2");
+
+        let mut iter = db.create_iterator(&read_op);
+        iter.seek("foo2");
+        let keys: Vec<_> = iter.into_iter().map(|item| item.unwrap().0).collect();
+        assert_eq!(keys, [b"foo2".to_vec().into_boxed_slice()]);
+
+        let view = db.read_view();
+        db.put(&write_op, "foo3", "bar3").unwrap();
+        assert_eq!(view.iter().count(), 2);
+    }
+
+    #[test]
+    fn test_iter_rev() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+
+        let write_op = WriteOptions::new();
+        db.put(&write_op, "foo1", "bar1").unwrap();
+        db.put(&write_op, "foo2", "bar2").unwrap();
+        db.put(&write_op, "foo3", "bar3").unwrap();
+
+        let read_op = ReadOptions::new();
+        let keys: Vec<_> = db
+            .iter_rev(&read_op)
+            .take(2)
+            .map(|item| item.unwrap().0)
+            .collect();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(&*keys[0], b"foo3");
+        assert_eq!(&*keys[1], b"foo2");
+
+        let mut iter = db.create_iterator(&read_op);
+        iter.seek_for_prev("foo2");
+        let keys: Vec<_> = iter
+            .into_iter_with(Direction::Reverse)
+            .map(|item| item.unwrap().0)
+            .collect();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(&*keys[0], b"foo2");
+        assert_eq!(&*keys[1], b"foo1");
+    }
+
+    #[test]
+    fn test_range() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+
+        let write_op = WriteOptions::new();
+        for key in ["a", "b", "c", "d"] {
+            db.put(&write_op, key, key).unwrap();
+        }
+
+        let keys = |iter: Iter| -> Vec<Box<[u8]>> { iter.map(|item| item.unwrap().0).collect() };
+        let boxed = |keys: &[&str]| -> Vec<Box<[u8]>> {
+            keys.iter().map(|key| key.as_bytes().into()).collect()
+        };
+
+        assert_eq!(
+            keys(db.range(ReadOptions::new(), b"b"..b"d")),
+            boxed(&["b", "c"])
+        );
+        assert_eq!(
+            keys(db.range(ReadOptions::new(), "b"..="c")),
+            boxed(&["b", "c"])
+        );
+        assert_eq!(
+            keys(db.range(ReadOptions::new(), "c"..)),
+            boxed(&["c", "d"])
+        );
+        assert_eq!(keys(db.range(ReadOptions::new(), .."b")), boxed(&["a"]));
+        assert_eq!(keys(db.range(ReadOptions::new(), ..)).len(), 4);
+        assert_eq!(
+            keys(db.range(
+                ReadOptions::new(),
+                (Bound::Excluded("a"), Bound::Included("c"))
+            )),
+            boxed(&["b", "c"])
+        );
+
+        let keys_only: Vec<_> = db
+            .range(ReadOptions::new(), "b"..)
+            .keys()
+            .map(|key| key.unwrap())
+            .collect();
+        assert_eq!(keys_only, boxed(&["b", "c", "d"]));
+
+        let view = db.read_view();
+        db.put(&write_op, "bb", "bb").unwrap();
+        assert_eq!(keys(view.range("b".."c")), boxed(&["b"]));
+    }
+
     #[test]
     fn test_iterator() {
         let path = DBPath::new();
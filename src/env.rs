@@ -0,0 +1,60 @@
+use librocksdb_sys::*;
+
+define!(
+    Env,
+    rocksdb_env_t,
+    rocksdb_create_default_env,
+    rocksdb_env_destroy
+);
+
+impl Env {
+    // keeps all files in memory, mostly useful for tests
+    pub fn mem() -> Self {
+        Self {
+            inner: unsafe { rocksdb_create_mem_env() },
+        }
+    }
+
+    pub fn set_background_threads(&mut self, n: i32) -> &mut Self {
+        unsafe { rocksdb_env_set_background_threads(self.inner, n) };
+        self
+    }
+
+    pub fn get_background_threads(&self) -> i32 {
+        unsafe { rocksdb_env_get_background_threads(self.inner) }
+    }
+
+    pub fn set_high_priority_background_threads(&mut self, n: i32) -> &mut Self {
+        unsafe { rocksdb_env_set_high_priority_background_threads(self.inner, n) };
+        self
+    }
+
+    pub fn get_high_priority_background_threads(&self) -> i32 {
+        unsafe { rocksdb_env_get_high_priority_background_threads(self.inner) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::options::tests::DBPath;
+    use crate::{Env, Options, DB};
+
+    #[test]
+    fn test_mem_env() {
+        let mut env = Env::mem();
+        env.set_background_threads(2);
+        assert_eq!(env.get_background_threads(), 2);
+
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_env(Box::leak(Box::new(env)));
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        db.put_default("foo", "bar").unwrap();
+        assert_eq!(db.get_default("foo").unwrap().unwrap(), b"bar");
+        assert!(!Path::new(path.as_ref()).exists());
+    }
+}
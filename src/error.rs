@@ -7,7 +7,7 @@ use crate::free;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Error(CString);
 
 impl Error {
@@ -17,8 +17,130 @@ impl Error {
         free(errptr);
         Self(err)
     }
+
+    pub(crate) fn invalid_argument(msg: &str) -> Self {
+        Self(CString::new(format!("Invalid argument: {}", msg)).unwrap_or_default())
+    }
+
+    pub(crate) fn corruption(msg: &str) -> Self {
+        Self(CString::new(format!("Corruption: {}", msg)).unwrap_or_default())
+    }
+
+    pub(crate) fn busy(msg: &str) -> Self {
+        Self(CString::new(format!("Resource busy: {}", msg)).unwrap_or_default())
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.parse().0
+    }
+
+    pub fn subcode(&self) -> SubCode {
+        self.parse().1
+    }
+
+    // The C API only hands back Status::ToString(), i.e. "<code>: <subcode>: <state>".
+    fn parse(&self) -> (ErrorKind, SubCode) {
+        let msg = self.0.to_bytes();
+        let (kind, rest) = KINDS
+            .iter()
+            .find_map(|&(prefix, kind)| Some((kind, msg.strip_prefix(prefix.as_bytes())?)))
+            .unwrap_or((ErrorKind::Unknown, msg));
+        let subcode = SUBCODES
+            .iter()
+            .find(|&&(prefix, _)| rest.starts_with(prefix.as_bytes()))
+            .map_or(SubCode::None, |&(_, subcode)| subcode);
+        (kind, subcode)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self(CString::new(format!("IO error: {}", e)).unwrap_or_default())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum ErrorKind {
+    NotFound = 1,
+    Corruption = 2,
+    NotSupported = 3,
+    InvalidArgument = 4,
+    IOError = 5,
+    MergeInProgress = 6,
+    Incomplete = 7,
+    ShutdownInProgress = 8,
+    TimedOut = 9,
+    Aborted = 10,
+    Busy = 11,
+    Expired = 12,
+    TryAgain = 13,
+    CompactionTooLarge = 14,
+    ColumnFamilyDropped = 15,
+    Unknown = u8::MAX,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum SubCode {
+    None = 0,
+    MutexTimeout = 1,
+    LockTimeout = 2,
+    LockLimit = 3,
+    NoSpace = 4,
+    Deadlock = 5,
+    StaleFile = 6,
+    MemoryLimit = 7,
+    SpaceLimit = 8,
+    PathNotFound = 9,
+    MergeOperandsInsufficientCapacity = 10,
+    ManualCompactionPaused = 11,
+    Overwritten = 12,
+    TxnNotPrepared = 13,
+    IOFenced = 14,
 }
 
+const KINDS: [(&str, ErrorKind); 15] = [
+    ("NotFound: ", ErrorKind::NotFound),
+    ("Corruption: ", ErrorKind::Corruption),
+    ("Not implemented: ", ErrorKind::NotSupported),
+    ("Invalid argument: ", ErrorKind::InvalidArgument),
+    ("IO error: ", ErrorKind::IOError),
+    ("Merge in progress: ", ErrorKind::MergeInProgress),
+    ("Result incomplete: ", ErrorKind::Incomplete),
+    ("Shutdown in progress: ", ErrorKind::ShutdownInProgress),
+    ("Operation timed out: ", ErrorKind::TimedOut),
+    ("Operation aborted: ", ErrorKind::Aborted),
+    ("Resource busy: ", ErrorKind::Busy),
+    ("Operation expired: ", ErrorKind::Expired),
+    ("Operation failed. Try again.: ", ErrorKind::TryAgain),
+    ("Compaction too large: ", ErrorKind::CompactionTooLarge),
+    ("Column family dropped: ", ErrorKind::ColumnFamilyDropped),
+];
+
+const SUBCODES: [(&str, SubCode); 14] = [
+    ("Timeout Acquiring Mutex", SubCode::MutexTimeout),
+    ("Timeout waiting to lock key", SubCode::LockTimeout),
+    (
+        "Failed to acquire lock due to max_num_locks limit",
+        SubCode::LockLimit,
+    ),
+    ("No space left on device", SubCode::NoSpace),
+    ("Deadlock", SubCode::Deadlock),
+    ("Stale file handle", SubCode::StaleFile),
+    ("Memory limit reached", SubCode::MemoryLimit),
+    ("Space limit reached", SubCode::SpaceLimit),
+    ("No such file or directory", SubCode::PathNotFound),
+    (
+        "Insufficient capacity for merge operands",
+        SubCode::MergeOperandsInsufficientCapacity,
+    ),
+    ("Manual compaction paused", SubCode::ManualCompactionPaused),
+    (" (overwritten)", SubCode::Overwritten),
+    ("Txn not prepared", SubCode::TxnNotPrepared),
+    ("IO fenced off", SubCode::IOFenced),
+];
+
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         Debug::fmt(&self.0, f)
@@ -26,3 +148,41 @@ impl Display for Error {
 }
 
 impl StdError for Error {}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::ffi::CString;
+
+    use super::Error;
+    use crate::{ErrorKind, SubCode};
+
+    pub(crate) fn error(msg: &str) -> Error {
+        Error(CString::new(msg).unwrap())
+    }
+
+    #[test]
+    fn test_kind() {
+        let e = error("Corruption: bad block");
+        assert_eq!(e.kind(), ErrorKind::Corruption);
+        assert_eq!(e.subcode(), SubCode::None);
+
+        let e = error("Operation timed out: Timeout waiting to lock key");
+        assert_eq!(e.kind(), ErrorKind::TimedOut);
+        assert_eq!(e.subcode(), SubCode::LockTimeout);
+
+        let e = error("Resource busy: Deadlock");
+        assert_eq!(e.kind(), ErrorKind::Busy);
+        assert_eq!(e.subcode(), SubCode::Deadlock);
+
+        let e = error("IO error: No space left on device: /tmp/db/000001.log");
+        assert_eq!(e.kind(), ErrorKind::IOError);
+        assert_eq!(e.subcode(), SubCode::NoSpace);
+        assert_eq!(e.kind() as u8, 5);
+
+        assert_eq!(error("something else").kind(), ErrorKind::Unknown);
+        assert_eq!(
+            Error::invalid_argument("foo").kind(),
+            ErrorKind::InvalidArgument
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::{Bytes, Iter, KeyRange, ReadOptions, Result, WriteBatch, WriteOptions, DB};
+
+// buckets[i] counts latencies below 2^i microseconds, the last one the rest
+const BUCKETS: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DbOp {
+    Get,
+    Put,
+    Delete,
+    Write,
+    // creating an iterator and positioning it at the start of the range
+    Seek,
+}
+
+const OPS: [DbOp; 5] = [DbOp::Get, DbOp::Put, DbOp::Delete, DbOp::Write, DbOp::Seek];
+
+// A DB that records a latency histogram and an error count per operation.
+pub struct InstrumentedDb {
+    db: DB,
+    stats: [Recorder; OPS.len()],
+}
+
+impl InstrumentedDb {
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            stats: Default::default(),
+        }
+    }
+
+    // operations on the inner db aren't recorded
+    pub fn db(&self) -> &DB {
+        &self.db
+    }
+
+    pub fn get(&self, options: &ReadOptions, key: impl AsRef<[u8]>) -> Result<Option<Bytes>> {
+        self.record(DbOp::Get, || self.db.get(options, key))
+    }
+
+    pub fn put(
+        &self,
+        options: &WriteOptions,
+        key: impl AsRef<[u8]>,
+        value: impl AsRef<[u8]>,
+    ) -> Result<()> {
+        self.record(DbOp::Put, || self.db.put(options, key, value))
+    }
+
+    pub fn delete(&self, options: &WriteOptions, key: impl AsRef<[u8]>) -> Result<()> {
+        self.record(DbOp::Delete, || self.db.delete(options, key))
+    }
+
+    pub fn write(&self, options: &WriteOptions, batch: &WriteBatch) -> Result<()> {
+        self.record(DbOp::Write, || self.db.write(options, batch))
+    }
+
+    // Errors surface while iterating and aren't counted.
+    pub fn range<'b>(&'b self, options: ReadOptions<'b>, range: impl KeyRange) -> Iter<'b> {
+        let start = Instant::now();
+        let iter = self.db.range(options, range);
+        self.stats[DbOp::Seek as usize].record(start.elapsed(), false);
+        iter
+    }
+
+    pub fn stats(&self, op: DbOp) -> OpStats {
+        self.stats[op as usize].snapshot()
+    }
+
+    pub fn reset_stats(&self) {
+        for recorder in &self.stats {
+            recorder.reset();
+        }
+    }
+
+    fn record<T>(&self, op: DbOp, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let start = Instant::now();
+        let result = f();
+        self.stats[op as usize].record(start.elapsed(), result.is_err());
+        result
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Recorder {
+    errors: AtomicU64,
+    total_micros: AtomicU64,
+    buckets: [AtomicU64; BUCKETS],
+}
+
+impl Recorder {
+    pub(crate) fn record(&self, latency: Duration, error: bool) {
+        let micros = latency.as_micros().min(u64::MAX as u128) as u64;
+        let bucket = ((u64::BITS - micros.leading_zeros()) as usize).min(BUCKETS - 1);
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.total_micros.fetch_add(micros, Ordering::Relaxed);
+        if error {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> OpStats {
+        let buckets = self.buckets.each_ref().map(|b| b.load(Ordering::Relaxed));
+        OpStats {
+            count: buckets.iter().sum(),
+            errors: self.errors.load(Ordering::Relaxed),
+            total: Duration::from_micros(self.total_micros.load(Ordering::Relaxed)),
+            buckets,
+        }
+    }
+
+    fn reset(&self) {
+        self.errors.store(0, Ordering::Relaxed);
+        self.total_micros.store(0, Ordering::Relaxed);
+        for bucket in &self.buckets {
+            bucket.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+// A point-in-time copy of the counters of one operation. The fields are read
+// one by one, so they may be off by the operations recorded meanwhile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpStats {
+    pub count: u64,
+    pub errors: u64,
+    pub total: Duration,
+    buckets: [u64; BUCKETS],
+}
+
+impl OpStats {
+    pub fn mean(&self) -> Duration {
+        match self.count {
+            0 => Duration::ZERO,
+            // the count may not fit in the u32 Duration divides by
+            n => Duration::from_nanos((self.total.as_nanos() / n as u128) as u64),
+        }
+    }
+
+    // Upper bound of the power-of-two bucket holding the p-th percentile,
+    // p in 0.0..=1.0.
+    pub fn percentile(&self, p: f64) -> Duration {
+        let rank = (self.count as f64 * p.clamp(0.0, 1.0)).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (i, n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                return Duration::from_micros(1 << i);
+            }
+        }
+        Duration::ZERO
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::options::tests::DBPath;
+    use crate::{DbOp, InstrumentedDb, Options, ReadOptions, WriteOptions, DB};
+
+    use super::Recorder;
+
+    #[test]
+    fn test_percentile() {
+        let recorder = Recorder::default();
+        for micros in [0, 3, 3, 100] {
+            recorder.record(Duration::from_micros(micros), false);
+        }
+        recorder.record(Duration::from_micros(1000), true);
+        let stats = recorder.snapshot();
+        assert_eq!(stats.count, 5);
+        assert_eq!(stats.errors, 1);
+        assert_eq!(stats.mean(), Duration::from_nanos(221_200));
+        assert_eq!(stats.percentile(0.0), Duration::from_micros(1));
+        assert_eq!(stats.percentile(0.5), Duration::from_micros(4));
+        assert_eq!(stats.percentile(1.0), Duration::from_micros(1024));
+    }
+
+    #[test]
+    fn test_mean_of_large_count() {
+        let stats = super::OpStats {
+            count: 1 << 33,
+            errors: 0,
+            total: Duration::from_micros(1 << 33),
+            buckets: Default::default(),
+        };
+        assert_eq!(stats.mean(), Duration::from_micros(1));
+    }
+
+    #[test]
+    fn test_instrumented_db() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = InstrumentedDb::new(DB::open(&options, path.as_ref()).unwrap());
+
+        db.put(&WriteOptions::new(), "key", "value").unwrap();
+        db.get(&ReadOptions::new(), "key").unwrap();
+        db.get(&ReadOptions::new(), "other").unwrap();
+        assert_eq!(db.range(ReadOptions::new(), ..).count(), 1);
+        assert_eq!(db.stats(DbOp::Put).count, 1);
+        assert_eq!(db.stats(DbOp::Get).count, 2);
+        assert_eq!(db.stats(DbOp::Seek).count, 1);
+        assert_eq!(db.stats(DbOp::Write).count, 0);
+
+        db.reset_stats();
+        assert_eq!(db.stats(DbOp::Get).count, 0);
+    }
+}
@@ -1,10 +1,13 @@
 use std::marker::PhantomData;
+use std::mem::forget;
+use std::ops::{Bound, Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
 use std::os::raw::c_char;
 use std::ptr::null_mut;
+use std::sync::Arc;
 
 use librocksdb_sys::*;
 
-use crate::{Error, Slice};
+use crate::{Error, ReadOptions, Result, Slice};
 
 pub struct Iterator<'a> {
     inner: *mut rocksdb_iterator_t,
@@ -19,6 +22,12 @@ impl<'a> Iterator<'a> {
         }
     }
 
+    pub(crate) fn into_raw(self) -> *mut rocksdb_iterator_t {
+        let inner = self.inner;
+        forget(self);
+        inner
+    }
+
     pub fn valid(&self) -> bool {
         unsafe { rocksdb_iter_valid(self.inner) != 0 }
     }
@@ -70,6 +79,29 @@ impl<'a> Iterator<'a> {
         let mut len: usize = 0;
         Slice::new(rocksdb_iter_value(self.inner, &mut len), len)
     }
+
+    pub fn item(&self) -> Option<(Slice<'_>, Slice<'_>)> {
+        if self.valid() {
+            Some(unsafe { (self.key(), self.value()) })
+        } else {
+            None
+        }
+    }
+
+    pub fn try_item(&self) -> Result<Option<(Slice<'_>, Slice<'_>)>> {
+        match self.item() {
+            Some(item) => Ok(Some(item)),
+            None => self.get_error().map_or(Ok(None), Err),
+        }
+    }
+
+    pub fn try_key(&self) -> Result<Option<Slice<'_>>> {
+        Ok(self.try_item()?.map(|(key, _)| key))
+    }
+
+    pub fn try_value(&self) -> Result<Option<Slice<'_>>> {
+        Ok(self.try_item()?.map(|(_, value)| value))
+    }
 }
 
 unsafe impl<'a> Send for Iterator<'a> {}
@@ -81,3 +113,284 @@ impl<'a> Drop for Iterator<'a> {
         unsafe { rocksdb_iter_destroy(self.inner) }
     }
 }
+
+impl<'a> IntoIterator for Iterator<'a> {
+    type Item = Result<(Box<[u8]>, Box<[u8]>)>;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        Iter::new(self, Direction::Forward)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Forward,
+    Reverse,
+}
+
+impl<'a> Iterator<'a> {
+    pub fn into_iter_with(self, direction: Direction) -> Iter<'a> {
+        Iter::new(self, direction)
+    }
+}
+
+// Yields entries starting at the current position of the underlying iterator.
+pub struct Iter<'a> {
+    raw: Iterator<'a>,
+    direction: Direction,
+    started: bool,
+    done: bool,
+    // Must be dropped after `raw`, which points into the iterate bounds.
+    _bounds: Option<OwnedBounds<'a>>,
+}
+
+impl<'a> Iter<'a> {
+    pub(crate) fn new(raw: Iterator<'a>, direction: Direction) -> Self {
+        Self {
+            raw,
+            direction,
+            started: false,
+            done: false,
+            _bounds: None,
+        }
+    }
+
+    // Bounds are translated for the default bytewise comparator.
+    pub(crate) fn range(
+        options: ReadOptions<'a>,
+        range: impl KeyRange,
+        create: impl FnOnce(&ReadOptions<'a>) -> Iterator<'a>,
+    ) -> Self {
+        let (start, end) = range.bounds();
+        let lower: Option<Box<[u8]>> = match start {
+            Bound::Included(key) => Some(key.into()),
+            Bound::Excluded(key) => Some([key, &[0]].concat().into()),
+            Bound::Unbounded => None,
+        };
+        let upper: Option<Box<[u8]>> = match end {
+            Bound::Included(key) => Some([key, &[0]].concat().into()),
+            Bound::Excluded(key) => Some(key.into()),
+            Bound::Unbounded => None,
+        };
+        unsafe {
+            if let Some(b) = &lower {
+                rocksdb_readoptions_set_iterate_lower_bound(options.inner, b.as_ptr() as _, b.len())
+            }
+            if let Some(b) = &upper {
+                rocksdb_readoptions_set_iterate_upper_bound(options.inner, b.as_ptr() as _, b.len())
+            }
+        }
+
+        let mut raw = create(&options);
+        match &lower {
+            Some(b) => raw.seek(b),
+            None => raw.seek_to_first(),
+        }
+        Self {
+            _bounds: Some(OwnedBounds {
+                _options: options,
+                _lower: lower,
+                _upper: upper,
+            }),
+            ..Self::new(raw, Direction::Forward)
+        }
+    }
+}
+
+struct OwnedBounds<'a> {
+    _options: ReadOptions<'a>,
+    _lower: Option<Box<[u8]>>,
+    _upper: Option<Box<[u8]>>,
+}
+
+pub trait KeyRange {
+    fn bounds(&self) -> (Bound<&[u8]>, Bound<&[u8]>);
+}
+
+impl KeyRange for RangeFull {
+    fn bounds(&self) -> (Bound<&[u8]>, Bound<&[u8]>) {
+        (Bound::Unbounded, Bound::Unbounded)
+    }
+}
+
+impl<T: AsRef<[u8]>> KeyRange for Range<T> {
+    fn bounds(&self) -> (Bound<&[u8]>, Bound<&[u8]>) {
+        (
+            Bound::Included(self.start.as_ref()),
+            Bound::Excluded(self.end.as_ref()),
+        )
+    }
+}
+
+impl<T: AsRef<[u8]>> KeyRange for RangeInclusive<T> {
+    fn bounds(&self) -> (Bound<&[u8]>, Bound<&[u8]>) {
+        (
+            Bound::Included(self.start().as_ref()),
+            Bound::Included(self.end().as_ref()),
+        )
+    }
+}
+
+impl<T: AsRef<[u8]>> KeyRange for RangeFrom<T> {
+    fn bounds(&self) -> (Bound<&[u8]>, Bound<&[u8]>) {
+        (Bound::Included(self.start.as_ref()), Bound::Unbounded)
+    }
+}
+
+impl<T: AsRef<[u8]>> KeyRange for RangeTo<T> {
+    fn bounds(&self) -> (Bound<&[u8]>, Bound<&[u8]>) {
+        (Bound::Unbounded, Bound::Excluded(self.end.as_ref()))
+    }
+}
+
+impl<T: AsRef<[u8]>> KeyRange for RangeToInclusive<T> {
+    fn bounds(&self) -> (Bound<&[u8]>, Bound<&[u8]>) {
+        (Bound::Unbounded, Bound::Included(self.end.as_ref()))
+    }
+}
+
+impl<T: AsRef<[u8]>> KeyRange for (Bound<T>, Bound<T>) {
+    fn bounds(&self) -> (Bound<&[u8]>, Bound<&[u8]>) {
+        (as_bytes(&self.0), as_bytes(&self.1))
+    }
+}
+
+fn as_bytes<T: AsRef<[u8]>>(bound: &Bound<T>) -> Bound<&[u8]> {
+    match bound {
+        Bound::Included(key) => Bound::Included(key.as_ref()),
+        Bound::Excluded(key) => Bound::Excluded(key.as_ref()),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+impl<'a> Iter<'a> {
+    pub fn keys(self) -> Keys<'a> {
+        Keys(self)
+    }
+
+    // Moves to the next entry, returning None once the scan is exhausted.
+    fn advance(&mut self) -> Option<Result<()>> {
+        if self.done {
+            return None;
+        }
+        if self.started {
+            match self.direction {
+                Direction::Forward => self.raw.next(),
+                Direction::Reverse => self.raw.prev(),
+            }
+        }
+        self.started = true;
+        if self.raw.valid() {
+            return Some(Ok(()));
+        }
+        self.done = true;
+        self.raw.get_error().map(Err)
+    }
+}
+
+impl<'a> std::iter::Iterator for Iter<'a> {
+    type Item = Result<(Box<[u8]>, Box<[u8]>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.advance()?.map(|_| {
+            let (key, value) = unsafe { (self.raw.key(), self.raw.value()) };
+            (key.as_ref().into(), value.as_ref().into())
+        }))
+    }
+}
+
+pub struct Keys<'a>(Iter<'a>);
+
+impl<'a> std::iter::Iterator for Keys<'a> {
+    type Item = Result<Box<[u8]>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(
+            self.0
+                .advance()?
+                .map(|_| unsafe { self.0.raw.key() }.as_ref().into()),
+        )
+    }
+}
+
+pub struct SharedIterator<DB> {
+    iter: Iterator<'static>,
+    // Must be dropped after `iter`, which points into the iterate bounds.
+    _options: ReadOptions<'static>,
+    _db: Arc<DB>,
+}
+
+impl<DB> SharedIterator<DB> {
+    pub(crate) fn new(
+        inner: *mut rocksdb_iterator_t,
+        options: ReadOptions<'static>,
+        db: Arc<DB>,
+    ) -> Self {
+        Self {
+            iter: Iterator::new(inner),
+            _options: options,
+            _db: db,
+        }
+    }
+
+    // Forwarded explicitly rather than through DerefMut, which would let the
+    // 'static iterator be swapped out of the wrapper and outlive the DB.
+    pub fn valid(&self) -> bool {
+        self.iter.valid()
+    }
+
+    pub fn get_error(&self) -> Option<Error> {
+        self.iter.get_error()
+    }
+
+    pub fn seek(&mut self, key: impl AsRef<[u8]>) {
+        self.iter.seek(key)
+    }
+
+    pub fn seek_for_prev(&mut self, key: impl AsRef<[u8]>) {
+        self.iter.seek_for_prev(key)
+    }
+
+    pub fn seek_to_first(&mut self) {
+        self.iter.seek_to_first()
+    }
+
+    pub fn seek_to_last(&mut self) {
+        self.iter.seek_to_last()
+    }
+
+    pub fn next(&mut self) {
+        self.iter.next()
+    }
+
+    pub fn prev(&mut self) {
+        self.iter.prev()
+    }
+
+    // REQUIRES: valid()
+    pub unsafe fn key(&self) -> Slice<'_> {
+        self.iter.key()
+    }
+
+    // REQUIRES: valid()
+    pub unsafe fn value(&self) -> Slice<'_> {
+        self.iter.value()
+    }
+
+    pub fn item(&self) -> Option<(Slice<'_>, Slice<'_>)> {
+        self.iter.item()
+    }
+
+    pub fn try_item(&self) -> Result<Option<(Slice<'_>, Slice<'_>)>> {
+        self.iter.try_item()
+    }
+
+    pub fn try_key(&self) -> Result<Option<Slice<'_>>> {
+        self.iter.try_key()
+    }
+
+    pub fn try_value(&self) -> Result<Option<Slice<'_>>> {
+        self.iter.try_value()
+    }
+}
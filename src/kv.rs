@@ -0,0 +1,132 @@
+use crate::options::default_write_options;
+use crate::{Bytes, ReadOnlyDB, ReadOptions, Result, Transaction, TransactionDB, DB};
+
+pub trait KvRead {
+    fn get(&self, options: &ReadOptions, key: impl AsRef<[u8]>) -> Result<Option<Bytes>>;
+
+    fn create_iterator(&self, options: &ReadOptions) -> crate::Iterator<'_>;
+}
+
+// Writes on DB and TransactionDB go through the default WriteOptions.
+pub trait KvWrite {
+    fn put(&self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) -> Result<()>;
+
+    fn delete(&self, key: impl AsRef<[u8]>) -> Result<()>;
+}
+
+impl KvRead for DB {
+    fn get(&self, options: &ReadOptions, key: impl AsRef<[u8]>) -> Result<Option<Bytes>> {
+        DB::get(self, options, key)
+    }
+
+    fn create_iterator(&self, options: &ReadOptions) -> crate::Iterator<'_> {
+        DB::create_iterator(self, options)
+    }
+}
+
+impl KvWrite for DB {
+    fn put(&self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) -> Result<()> {
+        DB::put(self, default_write_options(), key, value)
+    }
+
+    fn delete(&self, key: impl AsRef<[u8]>) -> Result<()> {
+        DB::delete(self, default_write_options(), key)
+    }
+}
+
+impl KvRead for ReadOnlyDB {
+    fn get(&self, options: &ReadOptions, key: impl AsRef<[u8]>) -> Result<Option<Bytes>> {
+        ReadOnlyDB::get(self, options, key)
+    }
+
+    fn create_iterator(&self, options: &ReadOptions) -> crate::Iterator<'_> {
+        ReadOnlyDB::create_iterator(self, options)
+    }
+}
+
+impl KvRead for TransactionDB {
+    fn get(&self, options: &ReadOptions, key: impl AsRef<[u8]>) -> Result<Option<Bytes>> {
+        TransactionDB::get(self, options, key)
+    }
+
+    fn create_iterator(&self, options: &ReadOptions) -> crate::Iterator<'_> {
+        TransactionDB::create_iterator(self, options)
+    }
+}
+
+impl KvWrite for TransactionDB {
+    fn put(&self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) -> Result<()> {
+        TransactionDB::put(self, default_write_options(), key, value)
+    }
+
+    fn delete(&self, key: impl AsRef<[u8]>) -> Result<()> {
+        TransactionDB::delete(self, default_write_options(), key)
+    }
+}
+
+impl<'a> KvRead for Transaction<'a> {
+    fn get(&self, options: &ReadOptions, key: impl AsRef<[u8]>) -> Result<Option<Bytes>> {
+        Transaction::get(self, options, key)
+    }
+
+    fn create_iterator(&self, options: &ReadOptions) -> crate::Iterator<'_> {
+        Transaction::create_iterator(self, options)
+    }
+}
+
+impl<'a> KvWrite for Transaction<'a> {
+    fn put(&self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) -> Result<()> {
+        Transaction::put(self, key, value)
+    }
+
+    fn delete(&self, key: impl AsRef<[u8]>) -> Result<()> {
+        Transaction::delete(self, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::options::tests::DBPath;
+    use crate::{KvRead, KvWrite, Options, ReadOptions, TransactionOptions, WriteOptions, DB};
+
+    fn rename(kv: &(impl KvRead + KvWrite), from: &str, to: &str) {
+        let read_op = ReadOptions::new();
+        let value = kv.get(&read_op, from).unwrap().unwrap();
+        kv.put(to, value).unwrap();
+        kv.delete(from).unwrap();
+    }
+
+    #[test]
+    fn test_db() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+
+        db.put(&WriteOptions::new(), "foo", "bar").unwrap();
+        rename(&db, "foo", "baz");
+
+        let read_op = ReadOptions::new();
+        assert!(db.get(&read_op, "foo").unwrap().is_none());
+        assert_eq!(db.get(&read_op, "baz").unwrap().unwrap().as_ref(), b"bar");
+    }
+
+    #[test]
+    fn test_transaction() {
+        let path = DBPath::new();
+        let db = crate::transaction_db::tests::open_new_db(path.as_ref());
+
+        let write_op = WriteOptions::new();
+        db.put(&write_op, "foo", "bar").unwrap();
+
+        let txn_op = TransactionOptions::new();
+        let txn = db.begin(&write_op, &txn_op, None);
+        rename(&txn, "foo", "baz");
+
+        let read_op = ReadOptions::new();
+        assert_eq!(db.get(&read_op, "foo").unwrap().unwrap().as_ref(), b"bar");
+        assert!(txn.commit().is_ok());
+        assert!(db.get(&read_op, "foo").unwrap().is_none());
+        assert_eq!(db.get(&read_op, "baz").unwrap().unwrap().as_ref(), b"bar");
+    }
+}
@@ -1,13 +1,46 @@
+use std::ffi::CString;
+use std::path::Path;
+
 use librocksdb_sys::rocksdb_free;
 
+pub use backup::*;
+pub use bulk_writer::*;
 pub use bytes::*;
+pub use cache::*;
+pub use cached_db::*;
+pub use column_family::ColumnFamily;
+pub use compaction_filter::{CompactionDecision, CompactionFilter};
+pub use counters::*;
 pub use db::*;
+pub use env::*;
 pub use error::*;
+pub use instrumented_db::*;
 pub use iterator::*;
+pub use kv::*;
+pub use log::*;
+pub use merge::*;
 pub use options::*;
+pub use read_only_db::*;
+pub use retry::*;
+pub use sampler::*;
+pub use slice_transform::*;
+pub use snapshot::*;
+pub use sst_file_writer::*;
+pub use statistics::{HistogramData, Statistics, StatisticsReport};
+#[cfg(feature = "async")]
+pub use stream::*;
+pub use table_properties::*;
+pub use throttle::*;
+#[cfg(feature = "tracing")]
+pub use trace::set_slow_op_threshold;
 pub use transaction::*;
 pub use transaction_db::*;
+pub use ttl::*;
+pub use typed::*;
+pub use wal_sync::*;
 pub use write_batch::*;
+pub use write_batch_with_index::*;
+pub use write_coalescer::*;
 
 macro_rules! ffi {
     ($f:ident($($args:expr),*)) => {{
@@ -35,6 +68,12 @@ macro_rules! define {
             }
         }
 
+        impl Default for $r {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
         impl Drop for $r {
             fn drop(&mut self) {
                 unsafe { $destroy(self.inner) }
@@ -47,16 +86,85 @@ macro_rules! define {
     };
 }
 
+pub mod admin;
+mod backup;
+#[cfg(feature = "bench")]
+pub mod bench;
+mod bulk_writer;
 mod bytes;
+mod cache;
+mod cached_db;
+mod column_family;
+mod compaction_filter;
+mod counters;
 mod db;
+mod env;
 mod error;
+mod instrumented_db;
 mod iterator;
+mod kv;
+mod log;
+mod merge;
 mod options;
+mod read_only_db;
+mod registry;
+mod retry;
+mod rng;
+mod sampler;
+mod slice_transform;
 mod snapshot;
+mod sst_file_writer;
+mod statistics;
+#[cfg(feature = "async")]
+mod stream;
+#[cfg(feature = "stress")]
+pub mod stress;
+mod table_properties;
+mod throttle;
+mod trace;
 mod transaction;
 mod transaction_db;
+mod ttl;
+mod typed;
+mod wal_sync;
 mod write_batch;
+mod write_batch_with_index;
+mod write_coalescer;
 
 fn free<T>(ptr: *mut T) {
     unsafe { rocksdb_free(ptr as _) };
 }
+
+// the smallest key greater than all keys starting with prefix, None if there
+// is none
+fn prefix_successor(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut limit = prefix.to_vec();
+    while let Some(last) = limit.pop() {
+        if last != 0xff {
+            limit.push(last + 1);
+            return Some(limit);
+        }
+    }
+    None
+}
+
+// Callback names only identify the callback, e.g. in the OPTIONS file, so
+// interior nul bytes are dropped instead of failing.
+fn name_to_cstring(name: &str) -> CString {
+    CString::new(name.replace('\0', "")).unwrap_or_default()
+}
+
+#[cfg(unix)]
+fn path_to_cstring(path: &Path) -> Result<CString> {
+    use std::os::unix::ffi::OsStrExt;
+    CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| Error::invalid_argument("path contains an interior nul byte"))
+}
+
+#[cfg(not(unix))]
+fn path_to_cstring(path: &Path) -> Result<CString> {
+    let path = path
+        .to_str()
+        .ok_or_else(|| Error::invalid_argument("path is not valid UTF-8"))?;
+    CString::new(path).map_err(|_| Error::invalid_argument("path contains an interior nul byte"))
+}
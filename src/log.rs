@@ -0,0 +1,256 @@
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::options::default_write_options;
+use crate::{Error, Iter, ReadOptions, Result, WriteBatch, DB};
+
+// An append-only log of values numbered by consecutive sequence numbers,
+// starting at 0. Entries are stored under the topic, prefixed by its length,
+// followed by the big-endian sequence number, so one db can hold many topics.
+// There must be only one Log per topic in a process, and nothing else may
+// write keys under the topic.
+pub struct Log<'a> {
+    db: &'a DB,
+    prefix: Vec<u8>,
+    // appends hold the lock across the write, so entries become visible in
+    // sequence order
+    next: Mutex<u64>,
+    appended: Condvar,
+}
+
+impl<'a> Log<'a> {
+    pub fn open(db: &'a DB, topic: impl AsRef<[u8]>) -> Result<Self> {
+        let topic = topic.as_ref();
+        let len = u32::try_from(topic.len())
+            .map_err(|_| Error::invalid_argument("log topic is too long"))?;
+        let mut log = Self {
+            db,
+            prefix: [&len.to_be_bytes(), topic].concat(),
+            next: Mutex::new(0),
+            appended: Condvar::new(),
+        };
+        let next = match log.last_sequence()? {
+            Some(sequence) => sequence + 1,
+            None => 0,
+        };
+        *log.next.get_mut().unwrap_or_else(PoisonError::into_inner) = next;
+        Ok(log)
+    }
+
+    // the key after all entries of the topic
+    fn end(&self) -> Vec<u8> {
+        [&self.prefix[..], &[0xff; 9]].concat()
+    }
+
+    // seeks straight to the newest entry instead of scanning the topic
+    fn last_sequence(&self) -> Result<Option<u64>> {
+        let end = self.end();
+        let mut options = ReadOptions::new();
+        options
+            .set_iterate_lower_bound(&self.prefix)
+            .set_iterate_upper_bound(&end);
+        let mut iter = self.db.create_iterator(&options);
+        iter.seek_to_last();
+        match iter.try_key()? {
+            Some(key) => parse_sequence(&key.as_ref()[self.prefix.len()..]).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn key(&self, sequence: u64) -> Vec<u8> {
+        [&self.prefix[..], &sequence.to_be_bytes()].concat()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, u64> {
+        self.next.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // Returns the sequence number of the entry.
+    pub fn append(&self, value: impl AsRef<[u8]>) -> Result<u64> {
+        self.append_all([value])
+            .map(|first| first.unwrap_or_default())
+    }
+
+    // Appends the values atomically, returns the sequence number of the
+    // first one, None if there are no values.
+    pub fn append_all<T: AsRef<[u8]>>(
+        &self,
+        values: impl IntoIterator<Item = T>,
+    ) -> Result<Option<u64>> {
+        let mut next = self.lock();
+        let mut batch = WriteBatch::new();
+        let mut sequence = *next;
+        for value in values {
+            batch.put(self.key(sequence), value);
+            sequence += 1;
+        }
+        if sequence == *next {
+            return Ok(None);
+        }
+        self.db.write(default_write_options(), &batch)?;
+        let first = *next;
+        *next = sequence;
+        self.appended.notify_all();
+        Ok(Some(first))
+    }
+
+    // the sequence number the next append gets
+    pub fn next_sequence(&self) -> u64 {
+        *self.lock()
+    }
+
+    // the oldest entry not truncated, None if the log is empty
+    pub fn first_sequence(&self) -> Result<Option<u64>> {
+        match self.entries(ReadOptions::new(), 0).next() {
+            Some(item) => Ok(Some(item?.0)),
+            None => Ok(None),
+        }
+    }
+
+    // Entries from `sequence` on, or from the first one if that was truncated.
+    pub fn read_from(
+        &self,
+        sequence: u64,
+    ) -> impl std::iter::Iterator<Item = Result<(u64, Box<[u8]>)>> + '_ {
+        self.entries(ReadOptions::new(), sequence)
+    }
+
+    fn entries(
+        &self,
+        options: ReadOptions<'a>,
+        sequence: u64,
+    ) -> impl std::iter::Iterator<Item = Result<(u64, Box<[u8]>)>> + 'a {
+        let iter: Iter<'a> = self.db.range(options, self.key(sequence)..self.end());
+        let prefix_len = self.prefix.len();
+        iter.map(move |item| {
+            let (key, value) = item?;
+            Ok((parse_sequence(&key[prefix_len..])?, value))
+        })
+    }
+
+    // Deletes the entries before `sequence`.
+    pub fn truncate_before(&self, sequence: u64) -> Result<()> {
+        let mut batch = WriteBatch::new();
+        batch.delete_range(self.key(0), self.key(sequence));
+        self.db.write(default_write_options(), &batch)
+    }
+
+    // A reader that waits for new entries, starting at `sequence`.
+    pub fn tail(&self, sequence: u64) -> LogTail<'_, 'a> {
+        LogTail {
+            log: self,
+            next: sequence,
+        }
+    }
+}
+
+pub struct LogTail<'l, 'a> {
+    log: &'l Log<'a>,
+    next: u64,
+}
+
+impl<'l, 'a> LogTail<'l, 'a> {
+    // Waits up to `timeout` for the next entry. Skips to the first entry if
+    // the log was truncated past the reader.
+    pub fn next_timeout(&mut self, timeout: Duration) -> Result<Option<(u64, Box<[u8]>)>> {
+        let deadline = Instant::now() + timeout;
+        let mut next = self.log.lock();
+        while *next <= self.next {
+            let timeout = match deadline.checked_duration_since(Instant::now()) {
+                Some(timeout) if !timeout.is_zero() => timeout,
+                _ => return Ok(None),
+            };
+            next = self
+                .log
+                .appended
+                .wait_timeout(next, timeout)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+        drop(next);
+        match self.log.read_from(self.next).next().transpose()? {
+            Some((sequence, value)) => {
+                self.next = sequence + 1;
+                Ok(Some((sequence, value)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    // the sequence number of the entry the reader returns next
+    pub fn position(&self) -> u64 {
+        self.next
+    }
+}
+
+fn parse_sequence(suffix: &[u8]) -> Result<u64> {
+    match suffix.try_into() {
+        Ok(sequence) => Ok(u64::from_be_bytes(sequence)),
+        Err(_) => Err(Error::corruption("log key has no sequence number")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::options::tests::DBPath;
+    use crate::{Log, Options, DB};
+
+    #[test]
+    fn test_log() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+
+        let log = Log::open(&db, "a").unwrap();
+        let other = Log::open(&db, "ab").unwrap();
+        assert_eq!(log.append("0").unwrap(), 0);
+        assert_eq!(log.append_all(["1", "2", "3"]).unwrap(), Some(1));
+        assert_eq!(log.append_all(Vec::<&str>::new()).unwrap(), None);
+        other.append("x").unwrap();
+
+        let entries: Vec<_> = log.read_from(2).map(|item| item.unwrap()).collect();
+        assert_eq!(entries, [(2, b"2"[..].into()), (3, b"3"[..].into())]);
+
+        log.truncate_before(2).unwrap();
+        assert_eq!(log.first_sequence().unwrap(), Some(2));
+        assert_eq!(log.read_from(0).count(), 2);
+        assert_eq!(other.read_from(0).count(), 1);
+
+        drop(log);
+        let log = Log::open(&db, "a").unwrap();
+        assert_eq!(log.next_sequence(), 4);
+        // a topic sorting between the others starts empty
+        assert_eq!(Log::open(&db, "b").unwrap().next_sequence(), 0);
+    }
+
+    #[test]
+    fn test_log_tail() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        let log = Log::open(&db, "topic").unwrap();
+        log.append_all(["0", "1"]).unwrap();
+        log.truncate_before(1).unwrap();
+
+        let mut tail = log.tail(0);
+        assert_eq!(tail.next_timeout(Duration::ZERO).unwrap().unwrap().0, 1);
+        assert!(tail
+            .next_timeout(Duration::from_millis(1))
+            .unwrap()
+            .is_none());
+        thread::scope(|s| {
+            s.spawn(|| {
+                thread::sleep(Duration::from_millis(20));
+                log.append("2").unwrap();
+            });
+            let (sequence, value) = tail.next_timeout(Duration::from_secs(5)).unwrap().unwrap();
+            assert_eq!((sequence, &*value), (2, &b"2"[..]));
+        });
+        assert_eq!(tail.position(), 3);
+    }
+}
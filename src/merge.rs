@@ -0,0 +1,461 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::CString;
+use std::marker::PhantomData;
+use std::os::raw::{c_char, c_int, c_uchar, c_void};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr::{null_mut, slice_from_raw_parts_mut};
+use std::slice;
+
+use librocksdb_sys::*;
+
+use crate::{name_to_cstring, Codec};
+
+// Combines merge operands with the existing value of a key. Returning None
+// fails the merge, the read or compaction that needed it then fails with a
+// Corruption error.
+pub trait MergeOperator: Send + Sync + 'static {
+    // Stored with the options in the OPTIONS file, changing the operator of
+    // an existing db needs a different name.
+    fn name(&self) -> &str;
+
+    fn full_merge(
+        &self,
+        key: &[u8],
+        existing: Option<&[u8]>,
+        operands: &[&[u8]],
+    ) -> Option<Vec<u8>>;
+
+    // Combines operands without the existing value. None leaves them as they
+    // are, which is always correct.
+    fn partial_merge(&self, _key: &[u8], _operands: &[&[u8]]) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+type FullMergeFn =
+    dyn Fn(&[u8], Option<&[u8]>, &[&[u8]]) -> Option<Vec<u8>> + Send + Sync + 'static;
+
+type PartialMergeFn = dyn Fn(&[u8], &[&[u8]]) -> Option<Vec<u8>> + Send + Sync + 'static;
+
+// A MergeOperator made of closures, with the same arguments as the trait
+// methods.
+pub struct MergeFn {
+    name: String,
+    full_merge: Box<FullMergeFn>,
+    partial_merge: Option<Box<PartialMergeFn>>,
+}
+
+impl MergeFn {
+    pub fn new(
+        name: impl Into<String>,
+        full_merge: impl Fn(&[u8], Option<&[u8]>, &[&[u8]]) -> Option<Vec<u8>> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            full_merge: Box::new(full_merge),
+            partial_merge: None,
+        }
+    }
+
+    pub fn set_partial_merge(
+        &mut self,
+        partial_merge: impl Fn(&[u8], &[&[u8]]) -> Option<Vec<u8>> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.partial_merge = Some(Box::new(partial_merge));
+        self
+    }
+}
+
+impl MergeOperator for MergeFn {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn full_merge(
+        &self,
+        key: &[u8],
+        existing: Option<&[u8]>,
+        operands: &[&[u8]],
+    ) -> Option<Vec<u8>> {
+        (self.full_merge)(key, existing, operands)
+    }
+
+    fn partial_merge(&self, key: &[u8], operands: &[&[u8]]) -> Option<Vec<u8>> {
+        self.partial_merge.as_ref()?(key, operands)
+    }
+}
+
+struct State {
+    name: CString,
+    operator: Box<dyn MergeOperator>,
+}
+
+// The options take ownership of the returned operator.
+pub(crate) fn create(operator: impl MergeOperator) -> *mut rocksdb_mergeoperator_t {
+    let state = Box::new(State {
+        name: name_to_cstring(operator.name()),
+        operator: Box::new(operator),
+    });
+    unsafe {
+        rocksdb_mergeoperator_create(
+            Box::into_raw(state) as _,
+            Some(destructor),
+            Some(full_merge),
+            Some(partial_merge),
+            Some(delete_value),
+            Some(name),
+        )
+    }
+}
+
+unsafe extern "C" fn destructor(state: *mut c_void) {
+    drop(Box::from_raw(state as *mut State));
+}
+
+unsafe fn operands<'a>(
+    list: *const *const c_char,
+    lengths: *const usize,
+    n: c_int,
+) -> Vec<&'a [u8]> {
+    (0..n.max(0) as usize)
+        .map(|i| slice::from_raw_parts(*list.add(i) as *const u8, *lengths.add(i)))
+        .collect()
+}
+
+// Hands the merged value to RocksDB, which copies it and gives it back to
+// delete_value.
+unsafe fn output(
+    value: Option<Vec<u8>>,
+    success: *mut c_uchar,
+    new_value_length: *mut usize,
+) -> *mut c_char {
+    match value {
+        Some(value) => {
+            *success = 1;
+            *new_value_length = value.len();
+            Box::into_raw(value.into_boxed_slice()) as *mut c_char
+        }
+        None => {
+            *success = 0;
+            *new_value_length = 0;
+            null_mut()
+        }
+    }
+}
+
+unsafe extern "C" fn full_merge(
+    state: *mut c_void,
+    key: *const c_char,
+    key_length: usize,
+    existing_value: *const c_char,
+    existing_value_length: usize,
+    operands_list: *const *const c_char,
+    operands_list_length: *const usize,
+    num_operands: c_int,
+    success: *mut c_uchar,
+    new_value_length: *mut usize,
+) -> *mut c_char {
+    let state = &*(state as *const State);
+    let key = slice::from_raw_parts(key as *const u8, key_length);
+    let existing = match existing_value.is_null() {
+        true => None,
+        false => Some(slice::from_raw_parts(
+            existing_value as *const u8,
+            existing_value_length,
+        )),
+    };
+    let operands = operands(operands_list, operands_list_length, num_operands);
+    // a panic must not unwind into RocksDB
+    let value = catch_unwind(AssertUnwindSafe(|| {
+        state.operator.full_merge(key, existing, &operands)
+    }));
+    output(value.ok().flatten(), success, new_value_length)
+}
+
+unsafe extern "C" fn partial_merge(
+    state: *mut c_void,
+    key: *const c_char,
+    key_length: usize,
+    operands_list: *const *const c_char,
+    operands_list_length: *const usize,
+    num_operands: c_int,
+    success: *mut c_uchar,
+    new_value_length: *mut usize,
+) -> *mut c_char {
+    let state = &*(state as *const State);
+    let key = slice::from_raw_parts(key as *const u8, key_length);
+    let operands = operands(operands_list, operands_list_length, num_operands);
+    let value = catch_unwind(AssertUnwindSafe(|| {
+        state.operator.partial_merge(key, &operands)
+    }));
+    output(value.ok().flatten(), success, new_value_length)
+}
+
+unsafe extern "C" fn delete_value(_state: *mut c_void, value: *const c_char, value_length: usize) {
+    if !value.is_null() {
+        drop(Box::from_raw(slice_from_raw_parts_mut(
+            value as *mut u8,
+            value_length,
+        )));
+    }
+}
+
+unsafe extern "C" fn name(state: *mut c_void) -> *const c_char {
+    (*(state as *const State)).name.as_ptr()
+}
+
+// fn() keeps the markers Send + Sync regardless of the type parameters
+type Marker<T> = PhantomData<fn() -> T>;
+
+// Values are lists encoded with C, every operand is a list appended to the
+// value.
+pub struct ListAppend<T, C> {
+    name: String,
+    _marker: Marker<(T, C)>,
+}
+
+// Values are sets encoded with C, every operand is a set added to the value.
+pub struct SetUnion<T, C> {
+    name: String,
+    _marker: Marker<(T, C)>,
+}
+
+// Values are maps encoded with C, every operand is a map whose entries are
+// inserted into the value, replacing the fields it already has.
+pub struct MapMerge<K, V, C> {
+    name: String,
+    _marker: Marker<(K, V, C)>,
+}
+
+// The default names are prefixed with the crate's name so they can't be
+// mistaken for operators built into RocksDB.
+macro_rules! structured_merge {
+    ($name:ident<$($p:ident),*>, $value:ty, $str:literal, $($bound:tt)*) => {
+        impl<$($p),*> $name<$($p),*> {
+            pub fn new() -> Self {
+                Self::with_name($str)
+            }
+
+            // RocksDB identifies the operator by its name, e.g. in the OPTIONS
+            // file.
+            pub fn with_name(name: impl Into<String>) -> Self {
+                Self {
+                    name: name.into(),
+                    _marker: PhantomData,
+                }
+            }
+        }
+
+        impl<$($p),*> Default for $name<$($p),*> {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<$($p: 'static),*> MergeOperator for $name<$($p),*>
+        where
+            C: Codec<$value>,
+            $($bound)*
+        {
+            fn name(&self) -> &str {
+                &self.name
+            }
+
+            fn full_merge(
+                &self,
+                _key: &[u8],
+                existing: Option<&[u8]>,
+                operands: &[&[u8]],
+            ) -> Option<Vec<u8>> {
+                let mut value = match existing {
+                    Some(existing) => C::decode(existing).ok()?,
+                    None => <$value>::new(),
+                };
+                for operand in operands {
+                    value.extend(C::decode(operand).ok()?);
+                }
+                C::encode(&value).ok()
+            }
+
+            fn partial_merge(&self, key: &[u8], operands: &[&[u8]]) -> Option<Vec<u8>> {
+                self.full_merge(key, None, operands)
+            }
+        }
+    };
+}
+
+structured_merge!(ListAppend<T, C>, Vec<T>, "luoshuqi-rocksdb.ListAppend",);
+structured_merge!(SetUnion<T, C>, BTreeSet<T>, "luoshuqi-rocksdb.SetUnion", T: Ord);
+structured_merge!(MapMerge<K, V, C>, BTreeMap<K, V>, "luoshuqi-rocksdb.MapMerge", K: Ord);
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::options::tests::DBPath;
+    use crate::{
+        BytesCodec, Codec, Error, ListAppend, MergeFn, MergeOperator, Options, Result, SetUnion,
+        WriteBatch, WriteOptions, DB,
+    };
+
+    fn add(existing: Option<&[u8]>, operands: &[&[u8]]) -> Option<Vec<u8>> {
+        let mut sum = u64::from_le_bytes(existing.unwrap_or(&[0; 8]).try_into().ok()?);
+        for operand in operands {
+            sum += u64::from_le_bytes((*operand).try_into().ok()?);
+        }
+        Some(sum.to_le_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_merge_fn() {
+        let mut operator = MergeFn::new("add", |_, existing, operands| add(existing, operands));
+        operator.set_partial_merge(|_, operands| add(None, operands));
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_merge_operator(operator);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+
+        let mut batch = WriteBatch::new();
+        batch.merge("n", 1u64.to_le_bytes());
+        batch.merge("n", 2u64.to_le_bytes());
+        db.write(&WriteOptions::new(), &batch).unwrap();
+        db.merge_default("n", 3u64.to_le_bytes()).unwrap();
+        assert_eq!(db.get_default("n").unwrap().unwrap(), 6u64.to_le_bytes());
+
+        // a malformed operand fails the merge
+        db.merge_default("n", "x").unwrap();
+        assert!(db.get_default("n").is_err());
+    }
+
+    #[test]
+    fn test_name_with_nul() {
+        let operator = MergeFn::new("a\0dd", |_, existing, operands| add(existing, operands));
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_merge_operator(operator);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        db.merge_default("n", 1u64.to_le_bytes()).unwrap();
+        assert_eq!(db.get_default("n").unwrap().unwrap(), 1u64.to_le_bytes());
+    }
+
+    // one byte per element
+    struct ByteSetCodec;
+
+    impl Codec<BTreeSet<u8>> for ByteSetCodec {
+        fn encode(value: &BTreeSet<u8>) -> Result<Vec<u8>> {
+            Ok(value.iter().copied().collect())
+        }
+
+        fn decode(bytes: &[u8]) -> Result<BTreeSet<u8>> {
+            Ok(bytes.iter().copied().collect())
+        }
+    }
+
+    #[test]
+    fn test_list_append() {
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_merge_operator(ListAppend::<u8, BytesCodec>::new());
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+
+        db.merge_default("key", "ab").unwrap();
+        db.merge_default("key", "c").unwrap();
+        assert_eq!(db.get_default("key").unwrap().unwrap(), b"abc");
+        db.put_default("key", "x").unwrap();
+        db.merge_default("key", "y").unwrap();
+        assert_eq!(db.get_default("key").unwrap().unwrap(), b"xy");
+    }
+
+    #[test]
+    fn test_structured_merge_name() {
+        assert_eq!(
+            ListAppend::<u8, BytesCodec>::new().name(),
+            "luoshuqi-rocksdb.ListAppend"
+        );
+        assert_eq!(
+            SetUnion::<u8, ByteSetCodec>::new().name(),
+            "luoshuqi-rocksdb.SetUnion"
+        );
+
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_merge_operator(ListAppend::<u8, BytesCodec>::with_name("append"));
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        db.merge_default("key", "a").unwrap();
+        db.merge_default("key", "b").unwrap();
+        assert_eq!(db.get_default("key").unwrap().unwrap(), b"ab");
+    }
+
+    #[test]
+    fn test_set_union() {
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_merge_operator(SetUnion::<u8, ByteSetCodec>::new());
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+
+        db.merge_default("key", "cab").unwrap();
+        db.merge_default("key", "bd").unwrap();
+        assert_eq!(db.get_default("key").unwrap().unwrap(), b"abcd");
+    }
+
+    #[test]
+    fn test_merge_failure() {
+        struct Fail;
+
+        impl Codec<BTreeSet<u8>> for Fail {
+            fn encode(_value: &BTreeSet<u8>) -> Result<Vec<u8>> {
+                Err(Error::invalid_argument("fail"))
+            }
+
+            fn decode(_bytes: &[u8]) -> Result<BTreeSet<u8>> {
+                Err(Error::corruption("fail"))
+            }
+        }
+
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_merge_operator(SetUnion::<u8, Fail>::new());
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        db.merge_default("key", "a").unwrap();
+        assert!(db.get_default("key").is_err());
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_map_merge() {
+        use std::collections::BTreeMap;
+
+        use crate::{BincodeCodec, MapMerge};
+
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_merge_operator(MapMerge::<String, u32, BincodeCodec>::new());
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+
+        let fields = |pairs: &[(&str, u32)]| {
+            let map: BTreeMap<String, u32> =
+                pairs.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+            BincodeCodec::encode(&map).unwrap()
+        };
+        db.merge_default("key", fields(&[("a", 1), ("b", 2)]))
+            .unwrap();
+        db.merge_default("key", fields(&[("b", 3)])).unwrap();
+        let value = db.get_default("key").unwrap().unwrap();
+        let map: BTreeMap<String, u32> = BincodeCodec::decode(&value).unwrap();
+        assert_eq!(map, [("a".to_string(), 1), ("b".to_string(), 3)].into());
+    }
+}
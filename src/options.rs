@@ -1,10 +1,18 @@
+use std::ffi::CString;
 use std::marker::PhantomData;
-use std::os::raw::c_uchar;
+use std::os::raw::{c_int, c_uchar};
+use std::path::Path;
 use std::ptr::null;
+use std::sync::OnceLock;
 
 use librocksdb_sys::*;
 
+use crate::compaction_filter::{self, CompactionFilter};
+use crate::merge::{self, MergeOperator};
+use crate::path_to_cstring;
+use crate::slice_transform::{self, SliceTransform};
 use crate::snapshot::Snapshot;
+use crate::{Cache, Env, Error, Result, Statistics};
 
 define!(
     Options,
@@ -14,21 +22,386 @@ define!(
 );
 
 impl Options {
-    pub fn set_create_if_missing(&mut self, create: bool) {
-        unsafe { rocksdb_options_set_create_if_missing(self.inner, create as c_uchar) }
+    // options not mentioned in `opts` are taken from `base`
+    pub fn from_string(base: &Options, opts: &str) -> Result<Options> {
+        let opts = CString::new(opts)
+            .map_err(|_| Error::invalid_argument("options string contains an interior nul byte"))?;
+        let options = Options::new();
+        ffi!(rocksdb_get_options_from_string(
+            base.inner,
+            opts.as_ptr(),
+            options.inner
+        ));
+        Ok(options)
+    }
+
+    pub fn set_create_if_missing(&mut self, create: bool) -> &mut Self {
+        unsafe { rocksdb_options_set_create_if_missing(self.inner, create as c_uchar) };
+        self
     }
 
     pub fn get_create_if_missing(&self) -> bool {
         unsafe { rocksdb_options_get_create_if_missing(self.inner) != 0 }
     }
 
-    pub fn set_error_if_exists(&self, error: bool) {
-        unsafe { rocksdb_options_set_error_if_exists(self.inner, error as _) }
+    pub fn set_create_missing_column_families(&mut self, create: bool) -> &mut Self {
+        unsafe {
+            rocksdb_options_set_create_missing_column_families(self.inner, create as c_uchar)
+        };
+        self
+    }
+
+    pub fn get_create_missing_column_families(&self) -> bool {
+        unsafe { rocksdb_options_get_create_missing_column_families(self.inner) != 0 }
+    }
+
+    pub fn set_error_if_exists(&mut self, error: bool) -> &mut Self {
+        unsafe { rocksdb_options_set_error_if_exists(self.inner, error as _) };
+        self
     }
 
     pub fn get_error_if_exists(&self) -> bool {
         unsafe { rocksdb_options_get_error_if_exists(self.inner) != 0 }
     }
+
+    // Options only keep a raw pointer to the env, so it must outlive every db
+    // opened with them.
+    pub fn set_env(&mut self, env: &'static Env) -> &mut Self {
+        unsafe { rocksdb_options_set_env(self.inner, env.inner) };
+        self
+    }
+
+    pub fn set_enable_blob_garbage_collection(&mut self, enable: bool) -> &mut Self {
+        unsafe { rocksdb_options_set_enable_blob_gc(self.inner, enable as c_uchar) };
+        self
+    }
+
+    pub fn get_enable_blob_garbage_collection(&self) -> bool {
+        unsafe { rocksdb_options_get_enable_blob_gc(self.inner) != 0 }
+    }
+
+    // fraction of the oldest blob files whose live blobs are relocated, 0.0 to 1.0
+    pub fn set_blob_gc_age_cutoff(&mut self, cutoff: f64) -> &mut Self {
+        unsafe { rocksdb_options_set_blob_gc_age_cutoff(self.inner, cutoff) };
+        self
+    }
+
+    pub fn get_blob_gc_age_cutoff(&self) -> f64 {
+        unsafe { rocksdb_options_get_blob_gc_age_cutoff(self.inner) }
+    }
+
+    // The C API has no setter for this one.
+    pub fn set_compaction_pri(&mut self, pri: CompactionPri) -> &mut Self {
+        let opts = match pri {
+            CompactionPri::ByCompensatedSize => "compaction_pri=kByCompensatedSize",
+            CompactionPri::OldestLargestSeqFirst => "compaction_pri=kOldestLargestSeqFirst",
+            CompactionPri::OldestSmallestSeqFirst => "compaction_pri=kOldestSmallestSeqFirst",
+            CompactionPri::MinOverlappingRatio => "compaction_pri=kMinOverlappingRatio",
+        };
+        self.set_from_string(opts)
+    }
+
+    // Options without a C setter go through the option string parser, the
+    // strings passed here are always valid.
+    fn set_from_string(&mut self, opts: &str) -> &mut Self {
+        *self = Options::from_string(self, opts).expect("invalid option string");
+        self
+    }
+
+    pub fn set_memtable_prefix_bloom_size_ratio(&mut self, ratio: f64) -> &mut Self {
+        unsafe { rocksdb_options_set_memtable_prefix_bloom_size_ratio(self.inner, ratio) };
+        self
+    }
+
+    pub fn get_memtable_prefix_bloom_size_ratio(&self) -> f64 {
+        unsafe { rocksdb_options_get_memtable_prefix_bloom_size_ratio(self.inner) }
+    }
+
+    // needs a non-zero memtable_prefix_bloom_size_ratio to have any effect
+    pub fn set_memtable_whole_key_filtering(&mut self, enable: bool) -> &mut Self {
+        unsafe { rocksdb_options_set_memtable_whole_key_filtering(self.inner, enable as c_uchar) };
+        self
+    }
+
+    // the table options are copied, later changes to them have no effect
+    pub fn set_block_based_table_factory(&mut self, options: &BlockBasedTableOptions) -> &mut Self {
+        unsafe { rocksdb_options_set_block_based_table_factory(self.inner, options.inner) };
+        self
+    }
+
+    // FixedPrefix and CappedPrefix are the common ones
+    pub fn set_prefix_extractor(&mut self, transform: impl SliceTransform) -> &mut Self {
+        unsafe {
+            rocksdb_options_set_prefix_extractor(self.inner, slice_transform::create(transform))
+        };
+        self
+    }
+
+    pub fn set_max_sequential_skip_in_iterations(&mut self, n: u64) -> &mut Self {
+        unsafe { rocksdb_options_set_max_sequential_skip_in_iterations(self.inner, n) };
+        self
+    }
+
+    pub fn get_max_sequential_skip_in_iterations(&self) -> u64 {
+        unsafe { rocksdb_options_get_max_sequential_skip_in_iterations(self.inner) }
+    }
+
+    pub fn set_writable_file_max_buffer_size(&mut self, size: u64) -> &mut Self {
+        unsafe { rocksdb_options_set_writable_file_max_buffer_size(self.inner, size) };
+        self
+    }
+
+    pub fn get_writable_file_max_buffer_size(&self) -> u64 {
+        unsafe { rocksdb_options_get_writable_file_max_buffer_size(self.inner) }
+    }
+
+    pub fn set_advise_random_on_open(&mut self, advise: bool) -> &mut Self {
+        unsafe { rocksdb_options_set_advise_random_on_open(self.inner, advise as c_uchar) };
+        self
+    }
+
+    pub fn get_advise_random_on_open(&self) -> bool {
+        unsafe { rocksdb_options_get_advise_random_on_open(self.inner) != 0 }
+    }
+
+    pub fn set_access_hint_on_compaction_start(&mut self, hint: AccessHint) -> &mut Self {
+        unsafe { rocksdb_options_set_access_hint_on_compaction_start(self.inner, hint as _) };
+        self
+    }
+
+    pub fn get_access_hint_on_compaction_start(&self) -> AccessHint {
+        match unsafe { rocksdb_options_get_access_hint_on_compaction_start(self.inner) } {
+            0 => AccessHint::None,
+            2 => AccessHint::Sequential,
+            3 => AccessHint::WillNeed,
+            _ => AccessHint::Normal,
+        }
+    }
+
+    // The C API has no setter for this one.
+    pub fn set_avoid_unnecessary_blocking_io(&mut self, avoid: bool) -> &mut Self {
+        self.set_from_string(if avoid {
+            "avoid_unnecessary_blocking_io=true"
+        } else {
+            "avoid_unnecessary_blocking_io=false"
+        })
+    }
+
+    pub fn set_max_successive_merges(&mut self, n: usize) -> &mut Self {
+        unsafe { rocksdb_options_set_max_successive_merges(self.inner, n) };
+        self
+    }
+
+    pub fn get_max_successive_merges(&self) -> usize {
+        unsafe { rocksdb_options_get_max_successive_merges(self.inner) }
+    }
+
+    // needed by IngestExternalFileOptions::set_ingest_behind
+    pub fn set_allow_ingest_behind(&mut self, allow: bool) -> &mut Self {
+        unsafe { rocksdb_options_set_allow_ingest_behind(self.inner, allow as c_uchar) };
+        self
+    }
+
+    pub fn get_allow_ingest_behind(&self) -> bool {
+        unsafe { rocksdb_options_get_allow_ingest_behind(self.inner) != 0 }
+    }
+
+    // paths are used in order, each filled up to its target size before
+    // moving on to the next
+    pub fn set_db_paths(&mut self, paths: &[DbPath]) -> &mut Self {
+        let mut paths: Vec<_> = paths.iter().map(|path| path.inner as *const _).collect();
+        unsafe { rocksdb_options_set_db_paths(self.inner, paths.as_mut_ptr(), paths.len()) };
+        self
+    }
+
+    pub fn set_stats_persist_period_sec(&mut self, period: u32) -> &mut Self {
+        unsafe { rocksdb_options_set_stats_persist_period_sec(self.inner, period) };
+        self
+    }
+
+    pub fn get_stats_persist_period_sec(&self) -> u32 {
+        unsafe { rocksdb_options_get_stats_persist_period_sec(self.inner) }
+    }
+
+    // The C API has no setter for this one. Without it, persisted stats are
+    // kept in memory only.
+    pub fn set_persist_stats_to_disk(&mut self, persist: bool) -> &mut Self {
+        self.set_from_string(if persist {
+            "persist_stats_to_disk=true"
+        } else {
+            "persist_stats_to_disk=false"
+        })
+    }
+
+    pub fn set_compression(&mut self, compression: CompressionType) -> &mut Self {
+        unsafe { rocksdb_options_set_compression(self.inner, compression as _) };
+        self
+    }
+
+    pub fn get_compression(&self) -> CompressionType {
+        CompressionType::from_raw(unsafe { rocksdb_options_get_compression(self.inner) })
+    }
+
+    pub fn set_bottommost_compression(&mut self, compression: CompressionType) -> &mut Self {
+        unsafe { rocksdb_options_set_bottommost_compression(self.inner, compression as _) };
+        self
+    }
+
+    // the MANIFEST is rolled over to a new file once it reaches this size
+    pub fn set_max_manifest_file_size(&mut self, size: usize) -> &mut Self {
+        unsafe { rocksdb_options_set_max_manifest_file_size(self.inner, size) };
+        self
+    }
+
+    pub fn get_max_manifest_file_size(&self) -> usize {
+        unsafe { rocksdb_options_get_max_manifest_file_size(self.inner) }
+    }
+
+    pub fn set_manifest_preallocation_size(&mut self, size: usize) -> &mut Self {
+        unsafe { rocksdb_options_set_manifest_preallocation_size(self.inner, size) };
+        self
+    }
+
+    pub fn get_manifest_preallocation_size(&self) -> usize {
+        unsafe { rocksdb_options_get_manifest_preallocation_size(self.inner) }
+    }
+
+    // obsolete files are still deleted after every compaction
+    pub fn set_delete_obsolete_files_period_micros(&mut self, micros: u64) -> &mut Self {
+        unsafe { rocksdb_options_set_delete_obsolete_files_period_micros(self.inner, micros) };
+        self
+    }
+
+    pub fn get_delete_obsolete_files_period_micros(&self) -> u64 {
+        unsafe { rocksdb_options_get_delete_obsolete_files_period_micros(self.inner) }
+    }
+
+    // There is no C setter on the block based table options for this one. It's
+    // applied to the table factory already set on these options, keeping its
+    // other settings, so call it after set_block_based_table_factory: a later
+    // set_block_based_table_factory replaces the factory and resets it.
+    pub fn set_optimize_filters_for_memory(&mut self, optimize: bool) -> &mut Self {
+        self.set_from_string(if optimize {
+            "block_based_table_factory={optimize_filters_for_memory=true}"
+        } else {
+            "block_based_table_factory={optimize_filters_for_memory=false}"
+        })
+    }
+
+    // caches whole key-value pairs, the cache is shared, not copied
+    pub fn set_row_cache(&mut self, cache: &Cache) -> &mut Self {
+        unsafe { rocksdb_options_set_row_cache(self.inner, cache.as_ptr()) };
+        self
+    }
+
+    pub fn set_merge_operator(&mut self, operator: impl MergeOperator) -> &mut Self {
+        unsafe { rocksdb_options_set_merge_operator(self.inner, merge::create(operator)) };
+        self
+    }
+
+    pub fn set_compaction_filter(&mut self, filter: impl CompactionFilter) -> &mut Self {
+        unsafe {
+            rocksdb_options_set_compaction_filter_factory(
+                self.inner,
+                compaction_filter::create(filter),
+            )
+        };
+        self
+    }
+
+    // Collects statistics for the dbs opened with these options, or copies of
+    // them made afterwards.
+    pub fn enable_statistics(&mut self) -> &mut Self {
+        unsafe { rocksdb_options_enable_statistics(self.inner) };
+        self
+    }
+
+    // None unless enable_statistics was called
+    pub fn get_statistics(&self) -> Option<Statistics> {
+        Statistics::new(self)
+    }
+
+    // presets from RocksDB, they overwrite a number of other options
+    pub fn prepare_for_bulk_load(&mut self) -> &mut Self {
+        unsafe { rocksdb_options_prepare_for_bulk_load(self.inner) };
+        self
+    }
+
+    pub fn optimize_for_point_lookup(&mut self, block_cache_size_mb: u64) -> &mut Self {
+        unsafe { rocksdb_options_optimize_for_point_lookup(self.inner, block_cache_size_mb) };
+        self
+    }
+
+    pub fn increase_parallelism(&mut self, total_threads: i32) -> &mut Self {
+        unsafe { rocksdb_options_increase_parallelism(self.inner, total_threads) };
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum AccessHint {
+    None = 0,
+    Normal = 1,
+    Sequential = 2,
+    WillNeed = 3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum ReadTier {
+    All = 0,
+    BlockCache = 1,
+    Persisted = 2,
+    Memtable = 3,
+}
+
+// Only the algorithms enabled through the crate features are available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum CompressionType {
+    None = 0,
+    #[cfg(feature = "snappy")]
+    Snappy = 1,
+    #[cfg(feature = "zlib")]
+    Zlib = 2,
+    #[cfg(feature = "bzip2")]
+    Bz2 = 3,
+    #[cfg(feature = "lz4")]
+    Lz4 = 4,
+    #[cfg(feature = "lz4")]
+    Lz4hc = 5,
+    #[cfg(feature = "zstd")]
+    Zstd = 7,
+}
+
+impl CompressionType {
+    // Anything not compiled in can't be used by the db either, report it as None.
+    fn from_raw(value: c_int) -> Self {
+        match value {
+            #[cfg(feature = "snappy")]
+            1 => Self::Snappy,
+            #[cfg(feature = "zlib")]
+            2 => Self::Zlib,
+            #[cfg(feature = "bzip2")]
+            3 => Self::Bz2,
+            #[cfg(feature = "lz4")]
+            4 => Self::Lz4,
+            #[cfg(feature = "lz4")]
+            5 => Self::Lz4hc,
+            #[cfg(feature = "zstd")]
+            7 => Self::Zstd,
+            _ => Self::None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompactionPri {
+    ByCompensatedSize,
+    OldestLargestSeqFirst,
+    OldestSmallestSeqFirst,
+    MinOverlappingRatio,
 }
 
 impl Clone for Options {
@@ -39,6 +412,126 @@ impl Clone for Options {
     }
 }
 
+define!(
+    BlockBasedTableOptions,
+    rocksdb_block_based_table_options_t,
+    rocksdb_block_based_options_create,
+    rocksdb_block_based_options_destroy
+);
+
+impl BlockBasedTableOptions {
+    pub fn set_whole_key_filtering(&mut self, enable: bool) -> &mut Self {
+        unsafe {
+            rocksdb_block_based_options_set_whole_key_filtering(self.inner, enable as c_uchar)
+        };
+        self
+    }
+
+    // the cache is shared, not copied
+    pub fn set_block_cache(&mut self, cache: &Cache) -> &mut Self {
+        unsafe { rocksdb_block_based_options_set_block_cache(self.inner, cache.as_ptr()) };
+        self
+    }
+
+    // number of keys between restart points for delta encoding of keys
+    pub fn set_block_restart_interval(&mut self, interval: i32) -> &mut Self {
+        unsafe { rocksdb_block_based_options_set_block_restart_interval(self.inner, interval) };
+        self
+    }
+
+    pub fn set_index_block_restart_interval(&mut self, interval: i32) -> &mut Self {
+        unsafe {
+            rocksdb_block_based_options_set_index_block_restart_interval(self.inner, interval)
+        };
+        self
+    }
+
+    // uncompressed size of the data blocks, 4KB by default
+    pub fn set_block_size(&mut self, size: usize) -> &mut Self {
+        unsafe { rocksdb_block_based_options_set_block_size(self.inner, size) };
+        self
+    }
+
+    pub fn set_no_block_cache(&mut self, disable: bool) -> &mut Self {
+        unsafe { rocksdb_block_based_options_set_no_block_cache(self.inner, disable as c_uchar) };
+        self
+    }
+
+    // A full bloom filter per table file, about 1% false positives at 10 bits
+    // per key.
+    pub fn set_bloom_filter(&mut self, bits_per_key: i32) -> &mut Self {
+        unsafe {
+            let policy = rocksdb_filterpolicy_create_bloom_full(bits_per_key);
+            rocksdb_block_based_options_set_filter_policy(self.inner, policy)
+        };
+        self
+    }
+
+    // Index and filter blocks are loaded into the block cache instead of being
+    // held by the open table files, so they count against its capacity.
+    pub fn set_cache_index_and_filter_blocks(&mut self, enable: bool) -> &mut Self {
+        unsafe {
+            rocksdb_block_based_options_set_cache_index_and_filter_blocks(
+                self.inner,
+                enable as c_uchar,
+            )
+        };
+        self
+    }
+
+    pub fn set_cache_index_and_filter_blocks_with_high_priority(
+        &mut self,
+        enable: bool,
+    ) -> &mut Self {
+        unsafe {
+            rocksdb_block_based_options_set_cache_index_and_filter_blocks_with_high_priority(
+                self.inner,
+                enable as c_uchar,
+            )
+        };
+        self
+    }
+
+    pub fn set_pin_l0_filter_and_index_blocks_in_cache(&mut self, pin: bool) -> &mut Self {
+        unsafe {
+            rocksdb_block_based_options_set_pin_l0_filter_and_index_blocks_in_cache(
+                self.inner,
+                pin as c_uchar,
+            )
+        };
+        self
+    }
+
+    // newer versions can't be read by older RocksDB releases
+    pub fn set_format_version(&mut self, version: i32) -> &mut Self {
+        unsafe { rocksdb_block_based_options_set_format_version(self.inner, version) };
+        self
+    }
+}
+
+pub struct DbPath {
+    inner: *mut rocksdb_dbpath_t,
+}
+
+impl DbPath {
+    pub fn new(path: impl AsRef<Path>, target_size: u64) -> Result<Self> {
+        let path = path_to_cstring(path.as_ref())?;
+        Ok(Self {
+            inner: unsafe { rocksdb_dbpath_create(path.as_ptr(), target_size) },
+        })
+    }
+}
+
+impl Drop for DbPath {
+    fn drop(&mut self) {
+        unsafe { rocksdb_dbpath_destroy(self.inner) }
+    }
+}
+
+unsafe impl Send for DbPath {}
+
+unsafe impl Sync for DbPath {}
+
 pub struct ReadOptions<'a> {
     pub(crate) inner: *mut rocksdb_readoptions_t,
     _marker: PhantomData<&'a ()>,
@@ -52,22 +545,96 @@ impl<'a> ReadOptions<'a> {
         }
     }
 
-    pub fn set_snapshot(&mut self, snapshot: &'a impl Snapshot) {
+    pub fn set_snapshot(&mut self, snapshot: &'a impl Snapshot) -> &mut Self {
         let ptr = match snapshot.as_ref() {
             Some(s) => s as *const _,
             None => null(),
         };
-        unsafe { rocksdb_readoptions_set_snapshot(self.inner, ptr) }
+        unsafe { rocksdb_readoptions_set_snapshot(self.inner, ptr) };
+        self
     }
 
-    pub fn set_iterate_upper_bound<T: AsRef<[u8]> + ?Sized>(&mut self, upper_bound: &'a T) {
+    pub fn set_iterate_upper_bound<T: AsRef<[u8]> + ?Sized>(
+        &mut self,
+        upper_bound: &'a T,
+    ) -> &mut Self {
         let b = upper_bound.as_ref();
-        unsafe { rocksdb_readoptions_set_iterate_upper_bound(self.inner, b.as_ptr() as _, b.len()) }
+        unsafe {
+            rocksdb_readoptions_set_iterate_upper_bound(self.inner, b.as_ptr() as _, b.len())
+        };
+        self
     }
 
-    pub fn set_iterate_lower_bound<T: AsRef<[u8]> + ?Sized>(&mut self, lower_bound: &'a T) {
+    pub fn set_iterate_lower_bound<T: AsRef<[u8]> + ?Sized>(
+        &mut self,
+        lower_bound: &'a T,
+    ) -> &mut Self {
         let b = lower_bound.as_ref();
-        unsafe { rocksdb_readoptions_set_iterate_lower_bound(self.inner, b.as_ptr() as _, b.len()) }
+        unsafe {
+            rocksdb_readoptions_set_iterate_lower_bound(self.inner, b.as_ptr() as _, b.len())
+        };
+        self
+    }
+
+    pub fn set_verify_checksums(&mut self, verify: bool) -> &mut Self {
+        unsafe { rocksdb_readoptions_set_verify_checksums(self.inner, verify as c_uchar) };
+        self
+    }
+
+    pub fn get_verify_checksums(&self) -> bool {
+        unsafe { rocksdb_readoptions_get_verify_checksums(self.inner) != 0 }
+    }
+
+    pub fn set_fill_cache(&mut self, fill: bool) -> &mut Self {
+        unsafe { rocksdb_readoptions_set_fill_cache(self.inner, fill as c_uchar) };
+        self
+    }
+
+    pub fn get_fill_cache(&self) -> bool {
+        unsafe { rocksdb_readoptions_get_fill_cache(self.inner) != 0 }
+    }
+
+    // with a prefix extractor, iteration stops at the first key with a
+    // different prefix than the seek key
+    pub fn set_prefix_same_as_start(&mut self, enable: bool) -> &mut Self {
+        unsafe { rocksdb_readoptions_set_prefix_same_as_start(self.inner, enable as c_uchar) };
+        self
+    }
+
+    pub fn get_prefix_same_as_start(&self) -> bool {
+        unsafe { rocksdb_readoptions_get_prefix_same_as_start(self.inner) != 0 }
+    }
+
+    // ignores the prefix extractor and seeks over all keys
+    pub fn set_total_order_seek(&mut self, enable: bool) -> &mut Self {
+        unsafe { rocksdb_readoptions_set_total_order_seek(self.inner, enable as c_uchar) };
+        self
+    }
+
+    pub fn get_total_order_seek(&self) -> bool {
+        unsafe { rocksdb_readoptions_get_total_order_seek(self.inner) != 0 }
+    }
+
+    // With ReadTier::BlockCache, reads that would have to go to disk fail with
+    // ErrorKind::Incomplete instead.
+    pub fn set_read_tier(&mut self, tier: ReadTier) -> &mut Self {
+        unsafe { rocksdb_readoptions_set_read_tier(self.inner, tier as _) };
+        self
+    }
+
+    pub fn get_read_tier(&self) -> ReadTier {
+        match unsafe { rocksdb_readoptions_get_read_tier(self.inner) } {
+            1 => ReadTier::BlockCache,
+            2 => ReadTier::Persisted,
+            3 => ReadTier::Memtable,
+            _ => ReadTier::All,
+        }
+    }
+}
+
+impl<'a> Default for ReadOptions<'a> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -81,6 +648,11 @@ unsafe impl<'a> Send for ReadOptions<'a> {}
 
 unsafe impl<'a> Sync for ReadOptions<'a> {}
 
+pub(crate) fn default_read_options() -> &'static ReadOptions<'static> {
+    static OPTIONS: OnceLock<ReadOptions<'static>> = OnceLock::new();
+    OPTIONS.get_or_init(ReadOptions::new)
+}
+
 define!(
     WriteOptions,
     rocksdb_writeoptions_t,
@@ -88,6 +660,61 @@ define!(
     rocksdb_writeoptions_destroy
 );
 
+impl WriteOptions {
+    pub fn set_disable_wal(&mut self, disable: bool) -> &mut Self {
+        unsafe { rocksdb_writeoptions_disable_WAL(self.inner, disable as _) };
+        self
+    }
+
+    pub fn get_disable_wal(&self) -> bool {
+        unsafe { rocksdb_writeoptions_get_disable_WAL(self.inner) != 0 }
+    }
+
+    pub fn set_sync(&mut self, sync: bool) -> &mut Self {
+        unsafe { rocksdb_writeoptions_set_sync(self.inner, sync as c_uchar) };
+        self
+    }
+
+    pub fn get_sync(&self) -> bool {
+        unsafe { rocksdb_writeoptions_get_sync(self.inner) != 0 }
+    }
+}
+
+impl Clone for WriteOptions {
+    fn clone(&self) -> Self {
+        let options = Self::new();
+        unsafe {
+            rocksdb_writeoptions_set_sync(options.inner, rocksdb_writeoptions_get_sync(self.inner));
+            rocksdb_writeoptions_disable_WAL(
+                options.inner,
+                rocksdb_writeoptions_get_disable_WAL(self.inner) as _,
+            );
+            rocksdb_writeoptions_set_ignore_missing_column_families(
+                options.inner,
+                rocksdb_writeoptions_get_ignore_missing_column_families(self.inner),
+            );
+            rocksdb_writeoptions_set_no_slowdown(
+                options.inner,
+                rocksdb_writeoptions_get_no_slowdown(self.inner),
+            );
+            rocksdb_writeoptions_set_low_pri(
+                options.inner,
+                rocksdb_writeoptions_get_low_pri(self.inner),
+            );
+            rocksdb_writeoptions_set_memtable_insert_hint_per_batch(
+                options.inner,
+                rocksdb_writeoptions_get_memtable_insert_hint_per_batch(self.inner),
+            );
+        }
+        options
+    }
+}
+
+pub(crate) fn default_write_options() -> &'static WriteOptions {
+    static OPTIONS: OnceLock<WriteOptions> = OnceLock::new();
+    OPTIONS.get_or_init(WriteOptions::new)
+}
+
 define!(
     FlushOptions,
     rocksdb_flushoptions_t,
@@ -96,8 +723,9 @@ define!(
 );
 
 impl FlushOptions {
-    pub fn set_wait(&mut self, wait: bool) {
-        unsafe { rocksdb_flushoptions_set_wait(self.inner, wait as c_uchar) }
+    pub fn set_wait(&mut self, wait: bool) -> &mut Self {
+        unsafe { rocksdb_flushoptions_set_wait(self.inner, wait as c_uchar) };
+        self
     }
 
     pub fn get_wait(&self) -> bool {
@@ -105,12 +733,125 @@ impl FlushOptions {
     }
 }
 
+impl Clone for FlushOptions {
+    fn clone(&self) -> Self {
+        let mut options = Self::new();
+        options.set_wait(self.get_wait());
+        options
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum BottommostLevelCompaction {
+    Skip = 0,
+    // only with a compaction filter
+    IfHaveCompactionFilter = 1,
+    Force = 2,
+    // like Force, but skips files created by this compaction
+    ForceOptimized = 3,
+}
+
+define!(
+    CompactRangeOptions,
+    rocksdb_compactoptions_t,
+    rocksdb_compactoptions_create,
+    rocksdb_compactoptions_destroy
+);
+
+impl CompactRangeOptions {
+    // no automatic compaction runs at the same time
+    pub fn set_exclusive_manual_compaction(&mut self, exclusive: bool) -> &mut Self {
+        unsafe {
+            rocksdb_compactoptions_set_exclusive_manual_compaction(self.inner, exclusive as c_uchar)
+        };
+        self
+    }
+
+    pub fn get_exclusive_manual_compaction(&self) -> bool {
+        unsafe { rocksdb_compactoptions_get_exclusive_manual_compaction(self.inner) != 0 }
+    }
+
+    // moves the compacted files to target_level, or the lowest level that
+    // fits them if that is -1
+    pub fn set_change_level(&mut self, change: bool) -> &mut Self {
+        unsafe { rocksdb_compactoptions_set_change_level(self.inner, change as c_uchar) };
+        self
+    }
+
+    pub fn get_change_level(&self) -> bool {
+        unsafe { rocksdb_compactoptions_get_change_level(self.inner) != 0 }
+    }
+
+    pub fn set_target_level(&mut self, level: i32) -> &mut Self {
+        unsafe { rocksdb_compactoptions_set_target_level(self.inner, level) };
+        self
+    }
+
+    pub fn get_target_level(&self) -> i32 {
+        unsafe { rocksdb_compactoptions_get_target_level(self.inner) }
+    }
+
+    pub fn set_bottommost_level_compaction(
+        &mut self,
+        compaction: BottommostLevelCompaction,
+    ) -> &mut Self {
+        unsafe {
+            rocksdb_compactoptions_set_bottommost_level_compaction(
+                self.inner,
+                compaction as c_uchar,
+            )
+        };
+        self
+    }
+
+    pub fn get_bottommost_level_compaction(&self) -> BottommostLevelCompaction {
+        match unsafe { rocksdb_compactoptions_get_bottommost_level_compaction(self.inner) } {
+            0 => BottommostLevelCompaction::Skip,
+            1 => BottommostLevelCompaction::IfHaveCompactionFilter,
+            3 => BottommostLevelCompaction::ForceOptimized,
+            _ => BottommostLevelCompaction::Force,
+        }
+    }
+}
+
+define!(
+    IngestExternalFileOptions,
+    rocksdb_ingestexternalfileoptions_t,
+    rocksdb_ingestexternalfileoptions_create,
+    rocksdb_ingestexternalfileoptions_destroy
+);
+
+impl IngestExternalFileOptions {
+    pub fn set_move_files(&mut self, move_files: bool) -> &mut Self {
+        unsafe {
+            rocksdb_ingestexternalfileoptions_set_move_files(self.inner, move_files as c_uchar)
+        };
+        self
+    }
+
+    // ingest into the bottommost level, below all existing data
+    pub fn set_ingest_behind(&mut self, ingest_behind: bool) -> &mut Self {
+        unsafe {
+            rocksdb_ingestexternalfileoptions_set_ingest_behind(
+                self.inner,
+                ingest_behind as c_uchar,
+            )
+        };
+        self
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
-    use std::fs::remove_dir_all;
+    use std::fs::{self, remove_dir_all};
     use std::time::{SystemTime, UNIX_EPOCH};
 
-    use crate::{Options, DB};
+    use crate::{
+        AccessHint, BlockBasedTableOptions, BottommostLevelCompaction, Cache, CompactRangeOptions,
+        CompactionPri, CompressionType, DbPath, ErrorKind, FlushOptions, Options, ReadOptions,
+        ReadTier, WriteOptions, DB,
+    };
 
     pub struct DBPath(String);
 
@@ -153,6 +894,16 @@ pub(crate) mod tests {
         assert!(DB::open(&options, path.as_ref()).is_ok());
     }
 
+    #[test]
+    fn test_options_chained_setters() {
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_error_if_exists(true);
+        assert!(options.get_create_if_missing());
+        assert!(options.get_error_if_exists());
+    }
+
     #[test]
     fn test_options_set_error_if_exists() {
         let mut options = Options::new();
@@ -168,4 +919,346 @@ pub(crate) mod tests {
         assert_eq!(options.get_error_if_exists(), true);
         assert!(DB::open(&options, path.as_ref()).is_err());
     }
+
+    #[test]
+    fn test_flush_options_wait() {
+        let mut options = FlushOptions::new();
+        options.set_wait(false);
+        assert!(!options.get_wait());
+        options.set_wait(true);
+        assert!(options.get_wait());
+    }
+
+    #[test]
+    fn test_flush_options_clone() {
+        let mut options = FlushOptions::default();
+        options.set_wait(false);
+        let cloned = options.clone();
+        options.set_wait(true);
+        assert!(!cloned.get_wait());
+    }
+
+    #[test]
+    fn test_options_clone() {
+        let mut options = Options::default();
+        options.set_create_if_missing(true);
+        let cloned = options.clone();
+        options.set_create_if_missing(false);
+        assert!(cloned.get_create_if_missing());
+    }
+
+    #[test]
+    fn test_options_from_string() {
+        let mut base = Options::new();
+        base.set_error_if_exists(true);
+        let options = Options::from_string(&base, "create_if_missing=true").unwrap();
+        assert!(options.get_create_if_missing());
+        assert!(options.get_error_if_exists());
+
+        assert!(Options::from_string(&base, "no_such_option=1").is_err());
+        assert!(Options::from_string(&base, "create_if_missing=\0").is_err());
+    }
+
+    #[test]
+    fn test_write_options_clone() {
+        let mut options = WriteOptions::default();
+        options.set_disable_wal(true);
+        assert!(options.get_disable_wal());
+        let cloned = options.clone();
+        options.set_disable_wal(false);
+        assert!(cloned.get_disable_wal());
+    }
+
+    #[test]
+    fn test_options_blob_gc() {
+        let mut options = Options::new();
+        options
+            .set_enable_blob_garbage_collection(true)
+            .set_blob_gc_age_cutoff(0.5);
+        assert!(options.get_enable_blob_garbage_collection());
+        assert_eq!(options.get_blob_gc_age_cutoff(), 0.5);
+    }
+
+    #[test]
+    fn test_options_compaction_pri() {
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_compaction_pri(CompactionPri::MinOverlappingRatio);
+        // other options survive the round trip through the option string
+        assert!(options.get_create_if_missing());
+        let path = DBPath::new();
+        assert!(DB::open(&options, path.as_ref()).is_ok());
+    }
+
+    #[test]
+    fn test_options_memtable_bloom() {
+        let mut table_options = BlockBasedTableOptions::new();
+        table_options.set_whole_key_filtering(true);
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_memtable_prefix_bloom_size_ratio(0.1)
+            .set_memtable_whole_key_filtering(true)
+            .set_block_based_table_factory(&table_options);
+        assert_eq!(options.get_memtable_prefix_bloom_size_ratio(), 0.1);
+
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        db.put_default("foo", "bar").unwrap();
+        assert_eq!(db.get_default("foo").unwrap().unwrap(), b"bar");
+        assert!(db.get_default("baz").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_options_max_sequential_skip_in_iterations() {
+        let mut options = Options::new();
+        options.set_max_sequential_skip_in_iterations(16);
+        assert_eq!(options.get_max_sequential_skip_in_iterations(), 16);
+    }
+
+    #[test]
+    fn test_options_writable_file_max_buffer_size() {
+        let mut options = Options::new();
+        options.set_writable_file_max_buffer_size(4 << 20);
+        assert_eq!(options.get_writable_file_max_buffer_size(), 4 << 20);
+    }
+
+    #[test]
+    fn test_options_access_hints() {
+        let mut options = Options::new();
+        options
+            .set_advise_random_on_open(false)
+            .set_access_hint_on_compaction_start(AccessHint::Sequential);
+        assert!(!options.get_advise_random_on_open());
+        assert_eq!(
+            options.get_access_hint_on_compaction_start(),
+            AccessHint::Sequential
+        );
+    }
+
+    #[test]
+    fn test_options_avoid_unnecessary_blocking_io() {
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_avoid_unnecessary_blocking_io(true);
+        assert!(options.get_create_if_missing());
+        let path = DBPath::new();
+        assert!(DB::open(&options, path.as_ref()).is_ok());
+    }
+
+    #[test]
+    fn test_options_max_successive_merges() {
+        let mut options = Options::new();
+        options.set_max_successive_merges(8);
+        assert_eq!(options.get_max_successive_merges(), 8);
+    }
+
+    #[test]
+    fn test_options_allow_ingest_behind() {
+        let mut options = Options::new();
+        options.set_allow_ingest_behind(true);
+        assert!(options.get_allow_ingest_behind());
+    }
+
+    #[test]
+    fn test_options_db_paths() {
+        let path = DBPath::new();
+        let hot = DBPath::new();
+        let cold = DBPath::new();
+        let mut options = Options::new();
+        options.set_create_if_missing(true).set_db_paths(&[
+            DbPath::new(hot.as_ref(), 1 << 20).unwrap(),
+            DbPath::new(cold.as_ref(), 1 << 30).unwrap(),
+        ]);
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        db.put_default("foo", "bar").unwrap();
+        db.flush(&FlushOptions::new()).unwrap();
+        assert_eq!(db.get_default("foo").unwrap().unwrap(), b"bar");
+    }
+
+    #[test]
+    fn test_options_persist_stats() {
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_stats_persist_period_sec(60)
+            .set_persist_stats_to_disk(true);
+        assert_eq!(options.get_stats_persist_period_sec(), 60);
+        let path = DBPath::new();
+        assert!(DB::open(&options, path.as_ref()).is_ok());
+    }
+
+    #[test]
+    fn test_read_options_read_tier() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        db.put(&WriteOptions::new(), "key", "value").unwrap();
+        db.flush(&FlushOptions::new()).unwrap();
+
+        let mut read_options = ReadOptions::new();
+        read_options.set_read_tier(ReadTier::BlockCache);
+        assert_eq!(read_options.get_read_tier(), ReadTier::BlockCache);
+        // the flushed block hasn't been read yet, so it can't be in the cache
+        let e = db.get(&read_options, "key").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Incomplete);
+
+        db.get(&ReadOptions::new(), "key").unwrap();
+        assert_eq!(db.get(&read_options, "key").unwrap().unwrap(), b"value");
+    }
+
+    #[test]
+    fn test_options_compression() {
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_compression(CompressionType::None)
+            .set_bottommost_compression(CompressionType::None);
+        assert_eq!(options.get_compression(), CompressionType::None);
+        #[cfg(feature = "zstd")]
+        {
+            options.set_bottommost_compression(CompressionType::Zstd);
+            options.set_compression(CompressionType::Zstd);
+            assert_eq!(options.get_compression(), CompressionType::Zstd);
+        }
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        db.put(&WriteOptions::new(), "key", "value").unwrap();
+        db.flush(&FlushOptions::new()).unwrap();
+    }
+
+    #[test]
+    fn test_options_manifest_sizes() {
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_max_manifest_file_size(64 << 10)
+            .set_manifest_preallocation_size(16 << 10);
+        assert_eq!(options.get_max_manifest_file_size(), 64 << 10);
+        assert_eq!(options.get_manifest_preallocation_size(), 16 << 10);
+        let path = DBPath::new();
+        assert!(DB::open(&options, path.as_ref()).is_ok());
+    }
+
+    #[test]
+    fn test_options_delete_obsolete_files_period() {
+        let mut options = Options::new();
+        options.set_delete_obsolete_files_period_micros(60_000_000);
+        assert_eq!(
+            options.get_delete_obsolete_files_period_micros(),
+            60_000_000
+        );
+    }
+
+    #[test]
+    fn test_block_based_table_restart_intervals() {
+        let mut table_options = BlockBasedTableOptions::new();
+        table_options
+            .set_block_restart_interval(32)
+            .set_index_block_restart_interval(4);
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_block_based_table_factory(&table_options);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        for i in 0..100 {
+            db.put(&WriteOptions::new(), format!("key{:03}", i), "value")
+                .unwrap();
+        }
+        db.flush(&FlushOptions::new()).unwrap();
+        assert!(db.get_default("key050").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_block_based_table_bloom_filter() {
+        let mut table_options = BlockBasedTableOptions::new();
+        table_options
+            .set_block_size(16 << 10)
+            .set_bloom_filter(10)
+            .set_cache_index_and_filter_blocks(true)
+            .set_cache_index_and_filter_blocks_with_high_priority(true)
+            .set_pin_l0_filter_and_index_blocks_in_cache(true)
+            .set_whole_key_filtering(true)
+            .set_format_version(5);
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_block_based_table_factory(&table_options);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        for i in 0..100 {
+            db.put(&WriteOptions::new(), format!("key{:03}", i), "value")
+                .unwrap();
+        }
+        db.flush(&FlushOptions::new()).unwrap();
+        assert!(db.table_properties().unwrap().filter_size > 0);
+        assert!(db.get_default("key050").unwrap().is_some());
+        assert!(db.get_default("key100").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_compact_range_options() {
+        let mut options = CompactRangeOptions::new();
+        options
+            .set_exclusive_manual_compaction(false)
+            .set_change_level(true)
+            .set_target_level(1)
+            .set_bottommost_level_compaction(BottommostLevelCompaction::ForceOptimized);
+        assert!(!options.get_exclusive_manual_compaction());
+        assert!(options.get_change_level());
+        assert_eq!(options.get_target_level(), 1);
+        assert_eq!(
+            options.get_bottommost_level_compaction(),
+            BottommostLevelCompaction::ForceOptimized
+        );
+    }
+
+    #[test]
+    fn test_options_optimize_filters_for_memory() {
+        let mut table_options = BlockBasedTableOptions::new();
+        table_options.set_block_restart_interval(32);
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_block_based_table_factory(&table_options)
+            .set_optimize_filters_for_memory(true);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        db.put(&WriteOptions::new(), "key", "value").unwrap();
+        db.flush(&FlushOptions::new()).unwrap();
+        assert!(db.get_default("key").unwrap().is_some());
+
+        // the flag and the earlier table options are both in the OPTIONS file
+        let options_file = fs::read_dir(path.as_ref())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                path.file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .starts_with("OPTIONS-")
+            })
+            .max()
+            .unwrap();
+        let contents = fs::read_to_string(options_file).unwrap();
+        assert!(contents.contains("optimize_filters_for_memory=true"));
+        assert!(contents.contains("block_restart_interval=32"));
+    }
+
+    #[test]
+    fn test_options_row_cache() {
+        let cache = Cache::new_lru(1 << 20);
+        let mut options = Options::new();
+        options.set_create_if_missing(true).set_row_cache(&cache);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        db.put(&WriteOptions::new(), "key", "value").unwrap();
+        db.flush(&FlushOptions::new()).unwrap();
+        db.get_default("key").unwrap().unwrap();
+        assert!(cache.get_usage() > 0);
+    }
 }
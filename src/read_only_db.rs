@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::path::Path;
+use std::ptr::null_mut;
+use std::sync::RwLock;
+
+use librocksdb_sys::*;
+
+use crate::column_family::RawColumnFamily;
+use crate::options::default_read_options;
+use crate::path_to_cstring;
+use crate::snapshot::OwnedSnapshot;
+use crate::{
+    Bytes, Direction, Iter, KeyRange, Options, PinnableSlice, ReadOptions, Result, SnapshotView, DB,
+};
+
+pub struct ReadOnlyDB {
+    db: DB,
+}
+
+impl ReadOnlyDB {
+    pub fn open(
+        options: &Options,
+        path: impl AsRef<Path>,
+        error_if_wal_file_exists: bool,
+    ) -> Result<Self> {
+        let path = path_to_cstring(path.as_ref())?;
+        // opened with the default family so that its handle is available
+        let name = CString::new("default").unwrap();
+        let mut handle = null_mut();
+        let inner = ffi!(rocksdb_open_for_read_only_column_families(
+            options.inner,
+            path.as_ptr(),
+            1,
+            &name.as_ptr(),
+            &(options.inner as *const _),
+            &mut handle,
+            error_if_wal_file_exists as _
+        ));
+        let default = RawColumnFamily::new(handle, inner, "default".to_string());
+        Ok(Self {
+            db: DB {
+                inner,
+                _registration: None,
+                column_families: RwLock::new(HashMap::from([("default".to_string(), default)])),
+            },
+        })
+    }
+
+    pub fn create_iterator(&self, options: &ReadOptions) -> crate::Iterator<'_> {
+        self.db.create_iterator(options)
+    }
+
+    pub fn iter(&self, options: &ReadOptions) -> Iter<'_> {
+        let mut iter = self.create_iterator(options);
+        iter.seek_to_first();
+        iter.into_iter()
+    }
+
+    pub fn iter_rev(&self, options: &ReadOptions) -> Iter<'_> {
+        let mut iter = self.create_iterator(options);
+        iter.seek_to_last();
+        iter.into_iter_with(Direction::Reverse)
+    }
+
+    pub fn range<'b>(&'b self, options: ReadOptions<'b>, range: impl KeyRange) -> Iter<'b> {
+        Iter::range(options, range, |options| self.create_iterator(options))
+    }
+
+    pub fn get(&self, options: &ReadOptions, key: impl AsRef<[u8]>) -> Result<Option<Bytes>> {
+        self.db.get(options, key)
+    }
+
+    pub fn get_pinned(
+        &self,
+        options: &ReadOptions,
+        key: impl AsRef<[u8]>,
+    ) -> Result<Option<PinnableSlice<'_>>> {
+        self.db.get_pinned(options, key)
+    }
+
+    pub fn multi_get(
+        &self,
+        options: &ReadOptions,
+        keys: &[impl AsRef<[u8]>],
+    ) -> Vec<Result<Option<Bytes>>> {
+        self.db.multi_get(options, keys)
+    }
+
+    pub fn try_multi_get(
+        &self,
+        options: &ReadOptions,
+        keys: &[impl AsRef<[u8]>],
+    ) -> Result<Vec<Option<Bytes>>> {
+        self.db.try_multi_get(options, keys)
+    }
+
+    pub fn create_snapshot(&self) -> OwnedSnapshot<'_, DB> {
+        self.db.create_snapshot()
+    }
+
+    pub fn read_view(&self) -> SnapshotView<'_> {
+        self.db.read_view()
+    }
+
+    pub fn create_iterator_default(&self) -> crate::Iterator<'_> {
+        self.create_iterator(default_read_options())
+    }
+
+    pub fn get_default(&self, key: impl AsRef<[u8]>) -> Result<Option<Bytes>> {
+        self.get(default_read_options(), key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::options::tests::DBPath;
+    use crate::{Options, ReadOnlyDB, ReadOptions, WriteOptions, DB};
+
+    #[test]
+    fn test_open() {
+        let options = Options::new();
+        let path = DBPath::new();
+        assert!(ReadOnlyDB::open(&options, path.as_ref(), false).is_err());
+
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        let write_op = WriteOptions::new();
+        db.put(&write_op, "foo", "bar").unwrap();
+        drop(db);
+
+        let db = ReadOnlyDB::open(&options, path.as_ref(), false).unwrap();
+        let read_op = ReadOptions::new();
+        assert_eq!(db.get(&read_op, "foo").unwrap().unwrap().as_ref(), b"bar");
+    }
+}
@@ -0,0 +1,155 @@
+// Process-wide registry of the paths opened by DB and TransactionDB. A second
+// open of the same path gets a Busy error up front instead of RocksDB's
+// IOError about the LOCK file. A path is marked as opening before RocksDB
+// opens it, so two threads can't race to open the same path, without holding
+// the registry lock while other paths are opened.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{self, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError, RwLock, Weak};
+
+use librocksdb_sys::rocksdb_t;
+
+use crate::db::ColumnFamilies;
+use crate::{Error, Result, DB};
+
+enum Entry {
+    Opening,
+    Exclusive,
+    Shared(Weak<DB>),
+}
+
+impl Entry {
+    // A shared db whose last reference is gone is being closed. It no longer
+    // counts as open, a new open may replace it.
+    fn is_live(&self) -> bool {
+        match self {
+            Entry::Shared(db) => db.strong_count() > 0,
+            _ => true,
+        }
+    }
+}
+
+// Entries are tagged with the registration that added them, so a closing db
+// doesn't remove the entry of a newer open of the same path.
+struct Slot {
+    id: u64,
+    entry: Entry,
+}
+
+// Removes the path from the registry when the owning db is dropped, which
+// happens after the db is closed, or when the open fails.
+pub(crate) struct Registration {
+    path: PathBuf,
+    id: u64,
+}
+
+impl Registration {
+    // marks the path as opening, fails if it is open or being opened
+    fn new(paths: &mut HashMap<PathBuf, Slot>, path: &Path) -> Result<Self> {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        let key = key(path);
+        if paths.get(&key).is_some_and(|slot| slot.entry.is_live()) {
+            return Err(already_open(path));
+        }
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        paths.insert(
+            key.clone(),
+            Slot {
+                id,
+                entry: Entry::Opening,
+            },
+        );
+        Ok(Self { path: key, id })
+    }
+
+    fn set(&mut self, entry: Entry) {
+        let mut paths = open_paths();
+        // the path may only exist now that it's open, which can change its key
+        let key = key(&self.path);
+        if key != self.path {
+            if paths.get(&self.path).is_some_and(|slot| slot.id == self.id) {
+                paths.remove(&self.path);
+            }
+            self.path = key;
+        }
+        paths.insert(self.path.clone(), Slot { id: self.id, entry });
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        let mut paths = open_paths();
+        if paths.get(&self.path).is_some_and(|slot| slot.id == self.id) {
+            paths.remove(&self.path);
+        }
+    }
+}
+
+fn open_paths() -> MutexGuard<'static, HashMap<PathBuf, Slot>> {
+    static OPEN_PATHS: OnceLock<Mutex<HashMap<PathBuf, Slot>>> = OnceLock::new();
+    OPEN_PATHS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+// A path that doesn't exist yet can't be open, the absolute path is only
+// used to look it up.
+fn key(path: &Path) -> PathBuf {
+    fs::canonicalize(path)
+        .or_else(|_| path::absolute(path))
+        .unwrap_or_else(|_| path.to_owned())
+}
+
+fn already_open(path: &Path) -> Error {
+    Error::busy(&format!(
+        "{} is already open in this process",
+        path.display()
+    ))
+}
+
+pub(crate) fn register<T>(
+    path: &Path,
+    open: impl FnOnce() -> Result<T>,
+) -> Result<(T, Registration)> {
+    let mut registration = Registration::new(&mut open_paths(), path)?;
+    let value = open()?;
+    registration.set(Entry::Exclusive);
+    Ok((value, registration))
+}
+
+pub(crate) fn register_shared(
+    path: &Path,
+    open: impl FnOnce() -> Result<(*mut rocksdb_t, ColumnFamilies)>,
+) -> Result<Arc<DB>> {
+    let mut registration = {
+        let mut paths = open_paths();
+        if let Some(Slot {
+            entry: Entry::Shared(db),
+            ..
+        }) = paths.get(&key(path))
+        {
+            if let Some(db) = db.upgrade() {
+                return Ok(db);
+            }
+        }
+        Registration::new(&mut paths, path)?
+    };
+    let (inner, column_families) = open()?;
+    // stays opening until there is a db to point to
+    registration.set(Entry::Opening);
+    let (path, id) = (registration.path.clone(), registration.id);
+    let db = Arc::new(DB {
+        inner,
+        _registration: Some(registration),
+        column_families: RwLock::new(column_families),
+    });
+    let mut paths = open_paths();
+    if let Some(slot) = paths.get_mut(&path).filter(|slot| slot.id == id) {
+        slot.entry = Entry::Shared(Arc::downgrade(&db));
+    }
+    Ok(db)
+}
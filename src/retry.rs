@@ -0,0 +1,139 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::thread;
+use std::time::Duration;
+
+use crate::{Error, ErrorKind, Result, SubCode};
+
+// Retries an operation that fails with a retryable error, sleeping a random
+// duration up to an exponentially growing backoff between attempts.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: usize,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    // a kind paired with a subcode only matches errors with that subcode
+    retryable: Vec<(ErrorKind, Option<SubCode>)>,
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        Self {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(100),
+            // of the timeouts only lock waits are worth retrying
+            retryable: vec![
+                (ErrorKind::Busy, None),
+                (ErrorKind::TimedOut, Some(SubCode::LockTimeout)),
+                (ErrorKind::TryAgain, None),
+            ],
+        }
+    }
+
+    // including the first attempt
+    pub fn set_max_attempts(&mut self, max_attempts: usize) -> &mut Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn set_backoff(&mut self, initial: Duration, max: Duration) -> &mut Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    // errors of these kinds are retried whatever their subcode
+    pub fn set_retryable(&mut self, kinds: &[ErrorKind]) -> &mut Self {
+        self.retryable = kinds.iter().map(|&kind| (kind, None)).collect();
+        self
+    }
+
+    pub fn is_retryable(&self, error: &Error) -> bool {
+        self.retryable.iter().any(|&(kind, subcode)| {
+            kind == error.kind() && (subcode.is_none() || subcode == Some(error.subcode()))
+        })
+    }
+
+    pub fn run<T>(&self, mut f: impl FnMut() -> Result<T>) -> Result<T> {
+        let mut backoff = self.initial_backoff;
+        let mut attempt = 1;
+        loop {
+            match f() {
+                Err(e) if attempt < self.max_attempts && self.is_retryable(&e) => {
+                    thread::sleep(jitter(backoff));
+                    backoff = backoff.saturating_mul(2).min(self.max_backoff);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// RandomState is randomly seeded, which is all the randomness needed here
+fn jitter(backoff: Duration) -> Duration {
+    let random = RandomState::new().build_hasher().finish();
+    let nanos = backoff.as_nanos() as u64;
+    Duration::from_nanos(if nanos == 0 { 0 } else { random % (nanos + 1) })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::error::tests::error;
+    use crate::{Error, ErrorKind, RetryPolicy};
+
+    #[test]
+    fn test_retry_policy() {
+        let mut policy = RetryPolicy::new();
+        policy
+            .set_max_attempts(3)
+            .set_backoff(Duration::ZERO, Duration::ZERO);
+
+        let mut attempts = 0;
+        let result = policy.run(|| {
+            attempts += 1;
+            if attempts < 3 {
+                Err(Error::busy("conflict"))
+            } else {
+                Ok(attempts)
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        attempts = 0;
+        let result: Result<(), _> = policy.run(|| {
+            attempts += 1;
+            Err(Error::busy("conflict"))
+        });
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::Busy);
+        assert_eq!(attempts, 3);
+
+        attempts = 0;
+        let result: Result<(), _> = policy.run(|| {
+            attempts += 1;
+            Err(Error::corruption("bad block"))
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn test_retryable_timeouts() {
+        let mut policy = RetryPolicy::new();
+        assert!(policy.is_retryable(&error("Operation timed out: Timeout waiting to lock key")));
+        assert!(!policy.is_retryable(&error("Operation timed out: ")));
+
+        policy.set_retryable(&[ErrorKind::TimedOut]);
+        assert!(policy.is_retryable(&error("Operation timed out: ")));
+        assert!(!policy.is_retryable(&Error::busy("conflict")));
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+
+use crate::prefix_successor;
+use crate::rng::Rng;
+use crate::{ReadOptions, Result, DB};
+
+// Walks the keyspace and keeps a uniform reservoir sample of the entries it
+// sees, to describe key and value sizes and find the prefixes holding most of
+// the keys, e.g. to decide where to split a shard.
+pub struct KeySampler {
+    sample_size: usize,
+    prefix_len: Option<usize>,
+    top_prefixes: usize,
+    max_scanned: Option<u64>,
+    seed: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct KeySpaceReport {
+    // exact if the whole keyspace was scanned, RocksDB's estimate otherwise
+    pub estimated_keys: u64,
+    pub estimated_live_bytes: u64,
+    pub scanned_keys: u64,
+    // false if the scan stopped at max_scanned, the sample then only covers
+    // the start of the keyspace
+    pub complete: bool,
+    pub key_sizes: SizeDistribution,
+    pub value_sizes: SizeDistribution,
+    // most keys first
+    pub top_prefixes: Vec<PrefixStats>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SizeDistribution {
+    pub min: usize,
+    pub max: usize,
+    pub mean: usize,
+    pub p50: usize,
+    pub p90: usize,
+    pub p99: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixStats {
+    pub prefix: Box<[u8]>,
+    // scaled up from the sample
+    pub estimated_keys: u64,
+    // of the table files only, the memtables aren't included
+    pub approximate_bytes: u64,
+}
+
+impl KeySampler {
+    pub fn new() -> Self {
+        Self {
+            sample_size: 10_000,
+            prefix_len: None,
+            top_prefixes: 10,
+            max_scanned: None,
+            seed: 0x9e37_79b9_7f4a_7c15,
+        }
+    }
+
+    pub fn set_sample_size(&mut self, size: usize) -> &mut Self {
+        self.sample_size = size.max(1);
+        self
+    }
+
+    // group keys by their first `len` bytes
+    pub fn set_prefix_len(&mut self, len: usize) -> &mut Self {
+        self.prefix_len = Some(len);
+        self
+    }
+
+    pub fn set_top_prefixes(&mut self, n: usize) -> &mut Self {
+        self.top_prefixes = n;
+        self
+    }
+
+    pub fn set_max_scanned(&mut self, n: u64) -> &mut Self {
+        self.max_scanned = Some(n);
+        self
+    }
+
+    pub fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn sample(&self, db: &DB) -> Result<KeySpaceReport> {
+        let mut rng = Rng::new(self.seed);
+        // (key prefix, key size, value size)
+        let mut sample: Vec<(Box<[u8]>, usize, usize)> = Vec::new();
+        let mut key_range = (usize::MAX, 0, 0u64);
+        let mut value_range = (usize::MAX, 0, 0u64);
+        let mut scanned = 0;
+        let mut complete = true;
+
+        let mut options = ReadOptions::new();
+        options.set_fill_cache(false);
+        for item in db.range(options, ..) {
+            if self.max_scanned.is_some_and(|max| scanned >= max) {
+                complete = false;
+                break;
+            }
+            let (key, value) = item?;
+            scanned += 1;
+            for (range, size) in [(&mut key_range, key.len()), (&mut value_range, value.len())] {
+                range.0 = range.0.min(size);
+                range.1 = range.1.max(size);
+                range.2 += size as u64;
+            }
+            let slot = if sample.len() < self.sample_size {
+                sample.len()
+            } else {
+                match rng.below(scanned) as usize {
+                    i if i < self.sample_size => i,
+                    _ => continue,
+                }
+            };
+            let prefix = match self.prefix_len {
+                Some(len) => key[..len.min(key.len())].into(),
+                None => Box::default(),
+            };
+            let entry = (prefix, key.len(), value.len());
+            match sample.get_mut(slot) {
+                Some(old) => *old = entry,
+                None => sample.push(entry),
+            }
+        }
+
+        let property = |name| -> Result<u64> {
+            Ok(db
+                .property_value(name)?
+                .and_then(|value| value.trim().parse().ok())
+                .unwrap_or(0))
+        };
+        let estimated_keys = match complete {
+            true => scanned,
+            false => property("rocksdb.estimate-num-keys")?,
+        };
+        let top_prefixes = match self.prefix_len {
+            Some(_) => self.top_prefixes(db, &sample, scanned)?,
+            None => Vec::new(),
+        };
+        Ok(KeySpaceReport {
+            estimated_keys,
+            estimated_live_bytes: property("rocksdb.estimate-live-data-size")?,
+            scanned_keys: scanned,
+            complete,
+            key_sizes: distribution(sample.iter().map(|s| s.1), key_range, scanned),
+            value_sizes: distribution(sample.iter().map(|s| s.2), value_range, scanned),
+            top_prefixes,
+        })
+    }
+
+    fn top_prefixes(
+        &self,
+        db: &DB,
+        sample: &[(Box<[u8]>, usize, usize)],
+        scanned: u64,
+    ) -> Result<Vec<PrefixStats>> {
+        let mut counts: HashMap<&[u8], u64> = HashMap::new();
+        for (prefix, _, _) in sample {
+            *counts.entry(prefix).or_default() += 1;
+        }
+        let mut counts: Vec<_> = counts.into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        counts.truncate(self.top_prefixes);
+
+        // a prefix of all 0xff bytes has no successor, any longer run of 0xff
+        // bytes is past every realistic key
+        let limits: Vec<_> = counts
+            .iter()
+            .map(|(prefix, _)| prefix_successor(prefix))
+            .collect();
+        let ranges: Vec<(&[u8], &[u8])> = counts
+            .iter()
+            .zip(&limits)
+            .map(|((prefix, _), limit)| (*prefix, limit.as_deref().unwrap_or(&[0xff; 64])))
+            .collect();
+        let sizes = db.approximate_sizes(&ranges)?;
+        Ok(counts
+            .into_iter()
+            .zip(sizes)
+            .map(|((prefix, count), approximate_bytes)| PrefixStats {
+                prefix: prefix.into(),
+                estimated_keys: count * scanned / sample.len() as u64,
+                approximate_bytes,
+            })
+            .collect())
+    }
+}
+
+impl Default for KeySampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// min and max are exact, the rest comes from the sample
+fn distribution(
+    sizes: impl Iterator<Item = usize>,
+    (min, max, total): (usize, usize, u64),
+    scanned: u64,
+) -> SizeDistribution {
+    let mut sizes: Vec<_> = sizes.collect();
+    if sizes.is_empty() {
+        return SizeDistribution::default();
+    }
+    sizes.sort_unstable();
+    let at = |p: f64| sizes[((sizes.len() - 1) as f64 * p).round() as usize];
+    SizeDistribution {
+        min,
+        max,
+        mean: (total / scanned) as usize,
+        p50: at(0.5),
+        p90: at(0.9),
+        p99: at(0.99),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::options::tests::DBPath;
+    use crate::{KeySampler, Options, DB};
+
+    #[test]
+    fn test_key_sampler() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        for i in 0..300u32 {
+            let prefix = if i < 200 { "hot" } else { "cold" };
+            db.put_default(format!("{}:{:04}", prefix, i), vec![0; i as usize % 10])
+                .unwrap();
+        }
+
+        let report = KeySampler::new()
+            .set_sample_size(50)
+            .set_prefix_len(3)
+            .sample(&db)
+            .unwrap();
+        assert!(report.complete);
+        assert_eq!(report.estimated_keys, 300);
+        assert_eq!(report.key_sizes.min, 8);
+        assert_eq!(report.key_sizes.max, 8);
+        assert_eq!(report.value_sizes.min, 0);
+        assert_eq!(report.value_sizes.max, 9);
+        assert_eq!(&*report.top_prefixes[0].prefix, b"hot");
+
+        let report = KeySampler::new().set_max_scanned(10).sample(&db).unwrap();
+        assert!(!report.complete);
+        assert_eq!(report.scanned_keys, 10);
+        assert!(report.top_prefixes.is_empty());
+    }
+}
@@ -0,0 +1,217 @@
+use std::ffi::CString;
+use std::os::raw::{c_char, c_uchar, c_void};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::slice;
+
+use librocksdb_sys::*;
+
+use crate::name_to_cstring;
+
+// Maps keys to prefixes for prefix bloom filters and prefix seeks. Only
+// keys in the domain have a prefix.
+pub trait SliceTransform: Send + Sync + 'static {
+    // Stored in the table files, a different name turns off their prefix
+    // filters.
+    fn name(&self) -> &str;
+
+    // length of the prefix of a key in the domain
+    fn prefix_len(&self, key: &[u8]) -> usize;
+
+    fn in_domain(&self, key: &[u8]) -> bool;
+}
+
+// The first n bytes, keys shorter than n are not in the domain.
+#[derive(Debug, Clone)]
+pub struct FixedPrefix {
+    len: usize,
+    name: String,
+}
+
+// The first n bytes, or the whole key if it is shorter.
+#[derive(Debug, Clone)]
+pub struct CappedPrefix {
+    len: usize,
+    name: String,
+}
+
+// The names match RocksDB's built-in transforms, so table files written with
+// either can use the other's prefix filters.
+impl FixedPrefix {
+    pub fn new(len: usize) -> Self {
+        let name = format!("rocksdb.FixedPrefix.{}", len);
+        Self { len, name }
+    }
+}
+
+impl CappedPrefix {
+    pub fn new(len: usize) -> Self {
+        let name = format!("rocksdb.CappedPrefix.{}", len);
+        Self { len, name }
+    }
+}
+
+impl SliceTransform for FixedPrefix {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn prefix_len(&self, _key: &[u8]) -> usize {
+        self.len
+    }
+
+    fn in_domain(&self, key: &[u8]) -> bool {
+        key.len() >= self.len
+    }
+}
+
+impl SliceTransform for CappedPrefix {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn prefix_len(&self, key: &[u8]) -> usize {
+        key.len().min(self.len)
+    }
+
+    fn in_domain(&self, _key: &[u8]) -> bool {
+        true
+    }
+}
+
+struct State {
+    name: CString,
+    transform: Box<dyn SliceTransform>,
+}
+
+// The options take ownership of the returned transform.
+pub(crate) fn create(transform: impl SliceTransform) -> *mut rocksdb_slicetransform_t {
+    let state = Box::new(State {
+        name: name_to_cstring(transform.name()),
+        transform: Box::new(transform),
+    });
+    unsafe {
+        rocksdb_slicetransform_create(
+            Box::into_raw(state) as _,
+            Some(destructor),
+            Some(transform_key),
+            Some(in_domain),
+            Some(in_range),
+            Some(name),
+        )
+    }
+}
+
+unsafe extern "C" fn destructor(state: *mut c_void) {
+    drop(Box::from_raw(state as *mut State));
+}
+
+// The prefix has to point into the key.
+unsafe extern "C" fn transform_key(
+    state: *mut c_void,
+    key: *const c_char,
+    length: usize,
+    dst_length: *mut usize,
+) -> *mut c_char {
+    let state = &*(state as *const State);
+    let slice = slice::from_raw_parts(key as *const u8, length);
+    // a panic must not unwind into RocksDB, the whole key is used instead
+    let len = catch_unwind(AssertUnwindSafe(|| state.transform.prefix_len(slice)));
+    *dst_length = len.unwrap_or(length).min(length);
+    key as *mut c_char
+}
+
+unsafe extern "C" fn in_domain(state: *mut c_void, key: *const c_char, length: usize) -> c_uchar {
+    let state = &*(state as *const State);
+    let key = slice::from_raw_parts(key as *const u8, length);
+    let in_domain = catch_unwind(AssertUnwindSafe(|| state.transform.in_domain(key)));
+    in_domain.unwrap_or(false) as c_uchar
+}
+
+// not used by RocksDB
+unsafe extern "C" fn in_range(_state: *mut c_void, _key: *const c_char, _length: usize) -> c_uchar {
+    0
+}
+
+unsafe extern "C" fn name(state: *mut c_void) -> *const c_char {
+    let state = &*(state as *const State);
+    state.name.as_ptr()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::options::tests::DBPath;
+    use crate::{
+        CappedPrefix, FixedPrefix, Options, ReadOptions, SliceTransform, WriteOptions, DB,
+    };
+
+    #[test]
+    fn test_builtin_transforms() {
+        let fixed = FixedPrefix::new(3);
+        assert_eq!(fixed.name(), "rocksdb.FixedPrefix.3");
+        assert!(fixed.in_domain(b"abc"));
+        assert!(!fixed.in_domain(b"ab"));
+        assert_eq!(fixed.prefix_len(b"abcd"), 3);
+
+        let capped = CappedPrefix::new(3);
+        assert_eq!(capped.name(), "rocksdb.CappedPrefix.3");
+        assert!(capped.in_domain(b"ab"));
+        assert_eq!(capped.prefix_len(b"ab"), 2);
+        assert_eq!(capped.prefix_len(b"abcd"), 3);
+    }
+
+    #[test]
+    fn test_prefix_seek() {
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_prefix_extractor(FixedPrefix::new(2));
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        for key in ["aa1", "aa2", "ab1", "b"] {
+            db.put(&WriteOptions::new(), key, "").unwrap();
+        }
+
+        let mut read_options = ReadOptions::new();
+        read_options.set_prefix_same_as_start(true);
+        assert!(read_options.get_prefix_same_as_start());
+        let mut iter = db.create_iterator(&read_options);
+        iter.seek("aa");
+        let keys: Vec<_> = iter.into_iter().map(|item| item.unwrap().0).collect();
+        assert_eq!(keys, [&b"aa1"[..], b"aa2"].map(Box::from));
+
+        let mut read_options = ReadOptions::new();
+        read_options.set_total_order_seek(true);
+        assert!(read_options.get_total_order_seek());
+        let mut iter = db.create_iterator(&read_options);
+        iter.seek("aa");
+        assert_eq!(iter.into_iter().count(), 4);
+    }
+
+    struct WholeKey;
+
+    impl SliceTransform for WholeKey {
+        fn name(&self) -> &str {
+            "Whole\0Key"
+        }
+
+        fn prefix_len(&self, key: &[u8]) -> usize {
+            key.len()
+        }
+
+        fn in_domain(&self, _key: &[u8]) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_name_with_nul() {
+        let mut options = Options::new();
+        options
+            .set_create_if_missing(true)
+            .set_prefix_extractor(WholeKey);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        db.put_default("key", "value").unwrap();
+        assert_eq!(db.get_default("key").unwrap().unwrap(), b"value");
+    }
+}
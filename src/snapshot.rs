@@ -1,4 +1,5 @@
 use std::marker::PhantomData;
+use std::sync::Arc;
 
 use librocksdb_sys::*;
 
@@ -33,6 +34,27 @@ unsafe impl<'a, DB: ReleaseSnapshot + Sync> Send for OwnedSnapshot<'a, DB> {}
 
 unsafe impl<'a, DB: ReleaseSnapshot + Sync> Sync for OwnedSnapshot<'a, DB> {}
 
+pub struct SharedSnapshot<DB: ReleaseSnapshot> {
+    pub(crate) inner: *const rocksdb_snapshot_t,
+    pub(crate) db: Arc<DB>,
+}
+
+impl<DB: ReleaseSnapshot> Snapshot for SharedSnapshot<DB> {
+    fn as_ref(&self) -> Option<&rocksdb_snapshot_t> {
+        Some(unsafe { &*self.inner })
+    }
+}
+
+impl<DB: ReleaseSnapshot> Drop for SharedSnapshot<DB> {
+    fn drop(&mut self) {
+        self.db.release_snapshot(self.inner)
+    }
+}
+
+unsafe impl<DB: ReleaseSnapshot + Send + Sync> Send for SharedSnapshot<DB> {}
+
+unsafe impl<DB: ReleaseSnapshot + Send + Sync> Sync for SharedSnapshot<DB> {}
+
 pub struct BorrowedSnapshot<'a> {
     inner: *const rocksdb_snapshot_t,
     _marker: PhantomData<&'a ()>,
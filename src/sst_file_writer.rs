@@ -0,0 +1,139 @@
+use std::path::Path;
+
+use librocksdb_sys::*;
+
+use crate::{path_to_cstring, Options, Result};
+
+// Writes a table file outside of any db, to be added to one with
+// DB::ingest_external_file. Keys have to be added in increasing order of the
+// comparator in the options.
+pub struct SstFileWriter {
+    inner: *mut rocksdb_sstfilewriter_t,
+    // the writer keeps pointers into its options, so they have to outlive it
+    _options: Options,
+}
+
+impl SstFileWriter {
+    // the options are copied, e.g. for compression and the table format
+    pub fn new(options: &Options) -> Self {
+        let options = options.clone();
+        unsafe {
+            let env_options = rocksdb_envoptions_create();
+            let inner = rocksdb_sstfilewriter_create(env_options, options.inner);
+            rocksdb_envoptions_destroy(env_options);
+            Self {
+                inner,
+                _options: options,
+            }
+        }
+    }
+
+    pub fn open(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path_to_cstring(path.as_ref())?;
+        Ok(ffi!(rocksdb_sstfilewriter_open(self.inner, path.as_ptr())))
+    }
+
+    pub fn put(&mut self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) -> Result<()> {
+        let key = key.as_ref();
+        let value = value.as_ref();
+        Ok(ffi!(rocksdb_sstfilewriter_put(
+            self.inner,
+            key.as_ptr() as _,
+            key.len(),
+            value.as_ptr() as _,
+            value.len()
+        )))
+    }
+
+    pub fn merge(&mut self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) -> Result<()> {
+        let key = key.as_ref();
+        let value = value.as_ref();
+        Ok(ffi!(rocksdb_sstfilewriter_merge(
+            self.inner,
+            key.as_ptr() as _,
+            key.len(),
+            value.as_ptr() as _,
+            value.len()
+        )))
+    }
+
+    pub fn delete(&mut self, key: impl AsRef<[u8]>) -> Result<()> {
+        let key = key.as_ref();
+        Ok(ffi!(rocksdb_sstfilewriter_delete(
+            self.inner,
+            key.as_ptr() as _,
+            key.len()
+        )))
+    }
+
+    // Fails if nothing was added. The writer can be opened again for the next
+    // file.
+    pub fn finish(&mut self) -> Result<()> {
+        Ok(ffi!(rocksdb_sstfilewriter_finish(self.inner)))
+    }
+
+    // size of the file so far
+    pub fn file_size(&self) -> u64 {
+        let mut size = 0;
+        unsafe { rocksdb_sstfilewriter_file_size(self.inner, &mut size) };
+        size
+    }
+}
+
+impl Drop for SstFileWriter {
+    fn drop(&mut self) {
+        unsafe { rocksdb_sstfilewriter_destroy(self.inner) }
+    }
+}
+
+unsafe impl Send for SstFileWriter {}
+
+#[cfg(test)]
+mod tests {
+    use crate::options::tests::DBPath;
+    use crate::{ErrorKind, IngestExternalFileOptions, Options, SstFileWriter, DB};
+
+    #[test]
+    fn test_sst_file_writer() {
+        let options = Options::new();
+        let dir = DBPath::new();
+        std::fs::create_dir_all(dir.as_ref()).unwrap();
+        let file = format!("{}/1.sst", dir.as_ref());
+
+        let mut writer = SstFileWriter::new(&options);
+        writer.open(&file).unwrap();
+        writer.delete("a").unwrap();
+        writer.put("b", "1").unwrap();
+        writer.put("c", "2").unwrap();
+        assert_eq!(
+            writer.put("b", "3").unwrap_err().kind(),
+            ErrorKind::InvalidArgument
+        );
+        writer.finish().unwrap();
+        assert!(writer.file_size() > 0);
+
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        db.put_default("a", "0").unwrap();
+        db.ingest_external_file(&IngestExternalFileOptions::new(), &[&file])
+            .unwrap();
+        assert!(db.get_default("a").unwrap().is_none());
+        assert_eq!(db.get_default("b").unwrap().unwrap(), b"1");
+        assert_eq!(db.get_default("c").unwrap().unwrap(), b"2");
+    }
+
+    #[test]
+    fn test_sst_file_writer_outlives_options() {
+        let dir = DBPath::new();
+        std::fs::create_dir_all(dir.as_ref()).unwrap();
+        let file = format!("{}/1.sst", dir.as_ref());
+
+        let mut writer = SstFileWriter::new(&Options::new());
+        writer.open(&file).unwrap();
+        writer.put("a", "1").unwrap();
+        writer.finish().unwrap();
+        assert!(writer.file_size() > 0);
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+use std::ffi::CStr;
+use std::str::FromStr;
+
+use librocksdb_sys::*;
+
+use crate::{free, Options};
+
+// Statistics of the dbs opened with a set of options, see
+// Options::enable_statistics. Tickers are counters like
+// "rocksdb.block.cache.hit", histograms are latency or size distributions like
+// "rocksdb.db.get.micros".
+#[derive(Clone)]
+pub struct Statistics {
+    // a copy of the options shares their statistics object
+    options: Options,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistogramData {
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub max: f64,
+    pub count: u64,
+    pub sum: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatisticsReport {
+    pub tickers: BTreeMap<String, u64>,
+    pub histograms: BTreeMap<String, HistogramData>,
+}
+
+impl Statistics {
+    pub(crate) fn new(options: &Options) -> Option<Self> {
+        let statistics = Self {
+            options: options.clone(),
+        };
+        statistics.raw_string().map(|_| statistics)
+    }
+
+    fn raw_string(&self) -> Option<String> {
+        let s = unsafe { rocksdb_options_statistics_get_string(self.options.inner) };
+        if s.is_null() {
+            return None;
+        }
+        let string = unsafe { CStr::from_ptr(s) }.to_string_lossy().into_owned();
+        free(s);
+        Some(string)
+    }
+
+    // one ticker or histogram per line
+    pub fn get_string(&self) -> String {
+        self.raw_string().unwrap_or_default()
+    }
+
+    pub fn report(&self) -> StatisticsReport {
+        StatisticsReport::parse(&self.get_string())
+    }
+
+    pub fn ticker(&self, name: &str) -> Option<u64> {
+        self.report().tickers.remove(name)
+    }
+
+    pub fn histogram(&self, name: &str) -> Option<HistogramData> {
+        self.report().histograms.remove(name)
+    }
+}
+
+impl StatisticsReport {
+    // Tickers are "name COUNT : n", histograms are
+    // "name P50 : x P95 : x P99 : x P100 : x COUNT : n SUM : n".
+    pub(crate) fn parse(s: &str) -> Self {
+        let mut report = Self::default();
+        for line in s.lines() {
+            let mut tokens = line.split_whitespace();
+            let name = match tokens.next() {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+            let tokens: Vec<_> = tokens.collect();
+            let fields: BTreeMap<_, _> = tokens
+                .chunks(3)
+                .filter(|chunk| chunk.len() == 3 && chunk[1] == ":")
+                .map(|chunk| (chunk[0], chunk[2]))
+                .collect();
+            if !fields.contains_key("COUNT") {
+                continue;
+            }
+            if fields.contains_key("P50") {
+                let histogram = HistogramData {
+                    p50: field(&fields, "P50"),
+                    p95: field(&fields, "P95"),
+                    p99: field(&fields, "P99"),
+                    max: field(&fields, "P100"),
+                    count: field(&fields, "COUNT"),
+                    sum: field(&fields, "SUM"),
+                };
+                report.histograms.insert(name, histogram);
+            } else {
+                report.tickers.insert(name, field(&fields, "COUNT"));
+            }
+        }
+        report
+    }
+}
+
+fn field<T: FromStr + Default>(fields: &BTreeMap<&str, &str>, key: &str) -> T {
+    fields
+        .get(key)
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::options::tests::DBPath;
+    use crate::statistics::StatisticsReport;
+    use crate::{HistogramData, Options, DB};
+
+    #[test]
+    fn test_parse() {
+        let s = "rocksdb.block.cache.miss COUNT : 3\n\
+                 rocksdb.block.cache.hit COUNT : 7\n\
+                 rocksdb.db.get.micros P50 : 1.500000 P95 : 3.000000 P99 : 4.000000 \
+                 P100 : 9.000000 COUNT : 10 SUM : 25\n";
+        let report = StatisticsReport::parse(s);
+        assert_eq!(report.tickers.len(), 2);
+        assert_eq!(report.tickers["rocksdb.block.cache.hit"], 7);
+        assert_eq!(
+            report.histograms["rocksdb.db.get.micros"],
+            HistogramData {
+                p50: 1.5,
+                p95: 3.0,
+                p99: 4.0,
+                max: 9.0,
+                count: 10,
+                sum: 25,
+            }
+        );
+    }
+
+    #[test]
+    fn test_statistics() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        assert!(options.get_statistics().is_none());
+        options.enable_statistics();
+        let statistics = options.get_statistics().unwrap();
+
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        for i in 0..10 {
+            db.put_default(format!("key{}", i), "value").unwrap();
+        }
+        for i in 0..5 {
+            db.get_default(format!("key{}", i)).unwrap();
+        }
+        assert!(statistics
+            .get_string()
+            .contains("rocksdb.number.keys.written"));
+        assert_eq!(statistics.ticker("rocksdb.number.keys.written"), Some(10));
+        assert_eq!(statistics.ticker("rocksdb.number.keys.read"), Some(5));
+        assert_eq!(
+            statistics.histogram("rocksdb.db.get.micros").unwrap().count,
+            5
+        );
+        assert!(statistics.ticker("no.such.ticker").is_none());
+    }
+}
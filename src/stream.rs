@@ -0,0 +1,168 @@
+use std::pin::Pin;
+use std::sync::mpsc::{sync_channel, Receiver, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::vec::IntoIter;
+
+use futures_core::Stream;
+
+use crate::{KeyRange, ReadOptions, Result, DB};
+
+type Item = Result<(Box<[u8]>, Box<[u8]>)>;
+
+pub struct ScanStream {
+    chunks: Receiver<Vec<Item>>,
+    waker: Arc<Mutex<Option<Waker>>>,
+    chunk: IntoIter<Item>,
+}
+
+impl ScanStream {
+    // The scan runs on its own thread so the async runtime is never blocked
+    // on rocksdb. The channel holds a single chunk, so besides the chunk being
+    // consumed at most two are read ahead: one queued and one waiting to be
+    // sent. A chunk size of zero is treated as one.
+    pub(crate) fn spawn(
+        db: Arc<DB>,
+        range: impl KeyRange + Send + 'static,
+        chunk_size: usize,
+    ) -> Self {
+        let chunk_size = chunk_size.max(1);
+        let (tx, rx) = sync_channel(1);
+        let waker = Arc::new(Mutex::new(None::<Waker>));
+        let producer_waker = waker.clone();
+        thread::spawn(move || {
+            let wake = || {
+                if let Some(waker) = producer_waker.lock().unwrap().take() {
+                    waker.wake();
+                }
+            };
+            let mut iter = db.range(ReadOptions::new(), range);
+            loop {
+                let chunk: Vec<_> = iter.by_ref().take(chunk_size).collect();
+                let done = chunk.len() < chunk_size;
+                if !chunk.is_empty() {
+                    if tx.send(chunk).is_err() {
+                        // stream dropped
+                        return;
+                    }
+                    wake();
+                }
+                if done {
+                    break;
+                }
+            }
+            drop(tx);
+            wake();
+        });
+        Self {
+            chunks: rx,
+            waker,
+            chunk: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl Stream for ScanStream {
+    type Item = Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Item>> {
+        let mut registered = false;
+        loop {
+            if let Some(item) = self.chunk.next() {
+                return Poll::Ready(Some(item));
+            }
+            match self.chunks.try_recv() {
+                Ok(chunk) => self.chunk = chunk.into_iter(),
+                Err(TryRecvError::Disconnected) => return Poll::Ready(None),
+                // check again after registering, the producer may have sent in between
+                Err(TryRecvError::Empty) if !registered => {
+                    *self.waker.lock().unwrap() = Some(cx.waker().clone());
+                    registered = true;
+                }
+                Err(TryRecvError::Empty) => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::thread::{self, Thread};
+
+    use futures_core::Stream;
+
+    use crate::options::tests::DBPath;
+    use crate::{Options, WriteOptions, DB};
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn collect<S: Stream + Unpin>(mut stream: S) -> Vec<S::Item> {
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut items = Vec::new();
+        loop {
+            match Pin::new(&mut stream).poll_next(&mut cx) {
+                Poll::Ready(Some(item)) => items.push(item),
+                Poll::Ready(None) => return items,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_stream_range() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = Arc::new(DB::open(&options, path.as_ref()).unwrap());
+        let options = WriteOptions::new();
+        for i in 0..10u8 {
+            db.put(&options, [i], [i]).unwrap();
+        }
+
+        let items: Vec<_> = collect(db.stream_range(vec![2u8]..vec![9u8], 3))
+            .into_iter()
+            .map(|item| item.unwrap())
+            .collect();
+        assert_eq!(items.len(), 7);
+        for (i, (key, value)) in (2..9u8).zip(items) {
+            assert_eq!(&*key, &[i]);
+            assert_eq!(&*value, &[i]);
+        }
+
+        assert!(collect(db.stream_range(vec![20u8].., 3)).is_empty());
+        assert_eq!(collect(db.stream_range(.., 0)).len(), 10);
+    }
+
+    #[test]
+    fn test_stream_prefix() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = Arc::new(DB::open(&options, path.as_ref()).unwrap());
+        let options = WriteOptions::new();
+        for key in [&b"a"[..], b"ab", b"ac", b"b", b"\xff", b"\xff\x01"] {
+            db.put(&options, key, key).unwrap();
+        }
+
+        let keys = |prefix: &[u8]| -> Vec<_> {
+            collect(db.stream_prefix(prefix, 2))
+                .into_iter()
+                .map(|item| item.unwrap().0)
+                .collect()
+        };
+        assert_eq!(keys(b"a"), [&b"a"[..], b"ab", b"ac"].map(Box::from));
+        assert_eq!(keys(b"\xff"), [&b"\xff"[..], b"\xff\x01"].map(Box::from));
+        assert!(keys(b"c").is_empty());
+    }
+}
@@ -0,0 +1,261 @@
+// Concurrency stress harness. Writers move balances between accounts in
+// transactions while readers check, under snapshots, that the accounts stay
+// complete, ordered and that the total never changes. With a non-zero
+// abort_probability the process aborts at random points; run it in a child
+// process and call verify on the same path afterwards to check recovery.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::rng::Rng;
+use crate::{
+    Error, Options, ReadOptions, Result, RetryPolicy, TransactionDB, TransactionDBOptions,
+};
+
+const INITIAL_BALANCE: u64 = 1000;
+
+#[derive(Debug, Clone)]
+pub struct StressConfig {
+    pub writers: usize,
+    pub readers: usize,
+    pub ops_per_thread: usize,
+    pub accounts: u64,
+    pub abort_probability: f64,
+    pub seed: u64,
+}
+
+impl Default for StressConfig {
+    fn default() -> Self {
+        Self {
+            writers: 4,
+            readers: 4,
+            ops_per_thread: 1000,
+            accounts: 64,
+            abort_probability: 0.0,
+            seed: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(1, |d| d.as_nanos() as u64),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct StressReport {
+    pub transfers: u64,
+    pub conflicts: u64,
+    pub snapshot_scans: u64,
+    pub point_reads: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    transfers: AtomicU64,
+    conflicts: AtomicU64,
+    snapshot_scans: AtomicU64,
+    point_reads: AtomicU64,
+}
+
+pub fn run(config: &StressConfig, path: impl AsRef<Path>) -> Result<StressReport> {
+    expected_total(config.accounts)?;
+    let db = open(path)?;
+    for account in 0..config.accounts {
+        if db.get_default(key(account))?.is_none() {
+            db.put_default(key(account), INITIAL_BALANCE.to_be_bytes())?;
+        }
+    }
+
+    let counters = Counters::default();
+    thread::scope(|scope| {
+        let mut handles = Vec::new();
+        for i in 0..config.writers {
+            let mut rng = Rng::new(config.seed.wrapping_add(i as u64));
+            let (db, counters) = (&db, &counters);
+            handles.push(scope.spawn(move || write_loop(db, config, &mut rng, counters)));
+        }
+        for i in 0..config.readers {
+            let mut rng = Rng::new(config.seed.wrapping_add((config.writers + i) as u64));
+            let (db, counters) = (&db, &counters);
+            handles.push(scope.spawn(move || read_loop(db, config, &mut rng, counters)));
+        }
+        handles.into_iter().try_for_each(|handle| {
+            handle
+                .join()
+                .unwrap_or_else(|_| Err(Error::corruption("stress thread panicked")))
+        })
+    })?;
+    check_total(&db, &ReadOptions::new(), config.accounts)?;
+
+    Ok(StressReport {
+        transfers: counters.transfers.into_inner(),
+        conflicts: counters.conflicts.into_inner(),
+        snapshot_scans: counters.snapshot_scans.into_inner(),
+        point_reads: counters.point_reads.into_inner(),
+    })
+}
+
+// checks the invariants of a db left behind by run, e.g. after an abort
+pub fn verify(config: &StressConfig, path: impl AsRef<Path>) -> Result<()> {
+    expected_total(config.accounts)?;
+    let db = open(path)?;
+    check_total(&db, &ReadOptions::new(), config.accounts)
+}
+
+fn open(path: impl AsRef<Path>) -> Result<TransactionDB> {
+    let mut options = Options::new();
+    options.set_create_if_missing(true);
+    TransactionDB::open(&options, &TransactionDBOptions::new(), path)
+}
+
+fn key(account: u64) -> [u8; 8] {
+    account.to_be_bytes()
+}
+
+fn balance(value: &[u8]) -> Result<u64> {
+    let bytes = value
+        .try_into()
+        .map_err(|_| Error::corruption("stress: balance is not 8 bytes"))?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
+fn write_loop(
+    db: &TransactionDB,
+    config: &StressConfig,
+    rng: &mut Rng,
+    counters: &Counters,
+) -> Result<()> {
+    // conflicts are expected under contention, keep retrying until one wins
+    let mut policy = RetryPolicy::new();
+    policy.set_max_attempts(usize::MAX);
+    let read_options = ReadOptions::new();
+    for _ in 0..config.ops_per_thread {
+        let from = rng.below(config.accounts);
+        let to = rng.below(config.accounts);
+        if from == to {
+            continue;
+        }
+        let mut first_attempt = true;
+        db.transaction(&policy, |txn| {
+            if !std::mem::take(&mut first_attempt) {
+                counters.conflicts.fetch_add(1, Ordering::Relaxed);
+            }
+            let from_balance = match txn.get_for_update(&read_options, key(from), true)? {
+                Some(value) => balance(&value)?,
+                None => return Err(Error::corruption("stress: account missing")),
+            };
+            let to_balance = match txn.get_for_update(&read_options, key(to), true)? {
+                Some(value) => balance(&value)?,
+                None => return Err(Error::corruption("stress: account missing")),
+            };
+            let amount = rng.below(from_balance + 1);
+            txn.put(key(from), (from_balance - amount).to_be_bytes())?;
+            txn.put(key(to), (to_balance + amount).to_be_bytes())
+        })?;
+        counters.transfers.fetch_add(1, Ordering::Relaxed);
+        if rng.chance(config.abort_probability) {
+            std::process::abort();
+        }
+    }
+    Ok(())
+}
+
+fn read_loop(
+    db: &TransactionDB,
+    config: &StressConfig,
+    rng: &mut Rng,
+    counters: &Counters,
+) -> Result<()> {
+    for _ in 0..config.ops_per_thread {
+        let snapshot = db.create_snapshot();
+        let mut options = ReadOptions::new();
+        options.set_snapshot(&snapshot);
+        if rng.chance(0.5) {
+            check_total(db, &options, config.accounts)?;
+            counters.snapshot_scans.fetch_add(1, Ordering::Relaxed);
+        } else {
+            // the same key read twice under one snapshot must not change
+            let account = key(rng.below(config.accounts));
+            let first = db.get(&options, account)?;
+            let second = db.get(&options, account)?;
+            if first.as_deref() != second.as_deref() {
+                return Err(Error::corruption("stress: snapshot read changed"));
+            }
+            counters.point_reads.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    Ok(())
+}
+
+// the sum of all balances, which transfers keep constant
+fn expected_total(accounts: u64) -> Result<u64> {
+    if accounts == 0 {
+        return Err(Error::invalid_argument("stress needs at least one account"));
+    }
+    accounts
+        .checked_mul(INITIAL_BALANCE)
+        .ok_or_else(|| Error::invalid_argument("stress: too many accounts"))
+}
+
+fn check_total(db: &TransactionDB, options: &ReadOptions, accounts: u64) -> Result<()> {
+    let expected = expected_total(accounts)?;
+    let mut total: u64 = 0;
+    let mut count = 0;
+    let mut last: Option<Box<[u8]>> = None;
+    for item in db.iter(options) {
+        let (key, value) = item?;
+        if last.as_ref().is_some_and(|last| *last >= key) {
+            return Err(Error::corruption("stress: iterator out of order"));
+        }
+        total = total
+            .checked_add(balance(&value)?)
+            .ok_or_else(|| Error::corruption("stress: total balance overflowed"))?;
+        count += 1;
+        last = Some(key);
+    }
+    if count != accounts {
+        return Err(Error::corruption("stress: wrong number of accounts"));
+    }
+    if total != expected {
+        return Err(Error::corruption("stress: total balance changed"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::options::tests::DBPath;
+    use crate::stress::{run, verify, StressConfig};
+    use crate::ErrorKind;
+
+    #[test]
+    fn test_stress() {
+        let config = StressConfig {
+            writers: 2,
+            readers: 2,
+            ops_per_thread: 200,
+            accounts: 16,
+            ..Default::default()
+        };
+        let path = DBPath::new();
+        let report = run(&config, path.as_ref()).unwrap();
+        assert!(report.transfers > 0);
+        assert_eq!(report.snapshot_scans + report.point_reads, 400);
+        verify(&config, path.as_ref()).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_accounts() {
+        for accounts in [0, u64::MAX] {
+            let config = StressConfig {
+                accounts,
+                ..Default::default()
+            };
+            let path = DBPath::new();
+            assert_eq!(
+                run(&config, path.as_ref()).unwrap_err().kind(),
+                ErrorKind::InvalidArgument
+            );
+        }
+    }
+}
@@ -0,0 +1,79 @@
+// Table properties summed over all live table files, parsed from the
+// rocksdb.aggregated-table-properties property.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableProperties {
+    pub num_data_blocks: u64,
+    pub num_entries: u64,
+    pub num_deletions: u64,
+    pub num_merge_operands: u64,
+    pub num_range_deletions: u64,
+    pub raw_key_size: u64,
+    pub raw_value_size: u64,
+    pub data_size: u64,
+    pub index_size: u64,
+    pub filter_size: u64,
+}
+
+impl TableProperties {
+    // The property is a list of "name=value" pairs separated by "; ". Unknown
+    // names are ignored.
+    pub(crate) fn parse(s: &str) -> Self {
+        let mut props = Self::default();
+        for pair in s.split("; ") {
+            let (name, value) = match pair.split_once('=') {
+                Some((name, value)) => (name.trim(), value.trim()),
+                None => continue,
+            };
+            let value = match value.parse() {
+                Ok(value) => value,
+                Err(_) => continue,
+            };
+            let field = match name {
+                "# data blocks" => &mut props.num_data_blocks,
+                "# entries" => &mut props.num_entries,
+                "# deletions" => &mut props.num_deletions,
+                "# merge operands" => &mut props.num_merge_operands,
+                "# range deletions" => &mut props.num_range_deletions,
+                "raw key size" => &mut props.raw_key_size,
+                "raw value size" => &mut props.raw_value_size,
+                "data block size" => &mut props.data_size,
+                "filter block size" => &mut props.filter_size,
+                // "index block size (user-key? 1, delta-value? 1)"
+                _ if name.starts_with("index block size") => &mut props.index_size,
+                _ => continue,
+            };
+            *field = value;
+        }
+        props
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::TableProperties;
+
+    #[test]
+    fn test_parse() {
+        let s = "# data blocks=2; # entries=100; # deletions=3; # merge operands=0; \
+                 # range deletions=1; raw key size=1200; raw average key size=12; \
+                 raw value size=500; raw average value size=5; data block size=4096; \
+                 index block size (user-key? 1, delta-value? 1)=64; # index partitions=0; \
+                 filter block size=128; (estimated) table size=4288; filter policy name=; ";
+        let props = TableProperties::parse(s);
+        assert_eq!(
+            props,
+            TableProperties {
+                num_data_blocks: 2,
+                num_entries: 100,
+                num_deletions: 3,
+                num_merge_operands: 0,
+                num_range_deletions: 1,
+                raw_key_size: 1200,
+                raw_value_size: 500,
+                data_size: 4096,
+                index_size: 64,
+                filter_size: 128,
+            }
+        );
+    }
+}
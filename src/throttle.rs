@@ -0,0 +1,126 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{Iter, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanRate {
+    // key and value bytes
+    BytesPerSec(u64),
+    KeysPerSec(u64),
+}
+
+// Sleeps between entries to keep a scan under a rate. Up to one second of
+// unused budget is saved up, so short pauses by the consumer aren't made up
+// for by a burst. The underlying iterator keeps its version of the db pinned
+// while sleeping.
+pub struct Throttled<I> {
+    inner: I,
+    rate: ScanRate,
+    credit: f64,
+    last: Instant,
+}
+
+impl<I> Throttled<I> {
+    pub fn new(inner: I, rate: ScanRate) -> Self {
+        Self {
+            inner,
+            rate,
+            credit: 0.0,
+            last: Instant::now(),
+        }
+    }
+
+    fn charge(&mut self, key: &[u8], value: &[u8]) {
+        let delay = self.delay(key.len() + value.len(), Instant::now());
+        if !delay.is_zero() {
+            thread::sleep(delay);
+        }
+    }
+
+    // how long to wait at `now` before an entry of `bytes` key and value bytes
+    fn delay(&mut self, bytes: usize, now: Instant) -> Duration {
+        let (rate, cost) = match self.rate {
+            ScanRate::BytesPerSec(rate) => (rate, bytes),
+            ScanRate::KeysPerSec(rate) => (rate, 1),
+        };
+        let rate = rate.max(1) as f64;
+        self.credit = (self.credit + rate * (now - self.last).as_secs_f64()).min(rate);
+        self.last = now;
+        self.credit -= cost as f64;
+        if self.credit < 0.0 {
+            Duration::from_secs_f64(-self.credit / rate)
+        } else {
+            Duration::ZERO
+        }
+    }
+}
+
+impl<I> std::iter::Iterator for Throttled<I>
+where
+    I: std::iter::Iterator<Item = Result<(Box<[u8]>, Box<[u8]>)>>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+        if let Ok((key, value)) = &item {
+            self.charge(key, value);
+        }
+        Some(item)
+    }
+}
+
+impl<'a> Iter<'a> {
+    pub fn throttled(self, rate: ScanRate) -> Throttled<Self> {
+        Throttled::new(self, rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::options::tests::DBPath;
+    use crate::{Options, ScanRate, Throttled, DB};
+
+    #[test]
+    fn test_delay() {
+        let start = Instant::now();
+        let mut throttled = Throttled::new((), ScanRate::KeysPerSec(4));
+        throttled.last = start;
+        assert_eq!(throttled.delay(10, start), Duration::from_millis(250));
+        assert_eq!(throttled.delay(10, start), Duration::from_millis(500));
+        // the wait for the earlier entries has passed
+        let later = start + Duration::from_millis(500);
+        assert_eq!(throttled.delay(10, later), Duration::from_millis(250));
+
+        // unused budget is capped at one second
+        let mut throttled = Throttled::new((), ScanRate::BytesPerSec(8));
+        throttled.last = start;
+        let idle = start + Duration::from_secs(10);
+        assert_eq!(throttled.delay(8, idle), Duration::ZERO);
+        assert_eq!(throttled.delay(4, idle), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_throttled_scan() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        for i in 0..100u32 {
+            db.put_default(i.to_be_bytes(), "value").unwrap();
+        }
+
+        let start = Instant::now();
+        let count = db.range_throttled(.., ScanRate::KeysPerSec(1000)).count();
+        assert_eq!(count, 100);
+        assert!(start.elapsed().as_millis() >= 90);
+
+        let count = db
+            .range_throttled(50u32.to_be_bytes().., ScanRate::BytesPerSec(1 << 20))
+            .count();
+        assert_eq!(count, 50);
+    }
+}
@@ -0,0 +1,177 @@
+#[cfg(feature = "tracing")]
+mod imp {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::{Duration, Instant};
+
+    use tracing::field::Empty;
+    use tracing::span::EnteredSpan;
+
+    static SLOW_OP_THRESHOLD_MICROS: AtomicU64 = AtomicU64::new(100_000);
+
+    // operations taking at least this long are also logged at warn level
+    pub fn set_slow_op_threshold(threshold: Duration) {
+        SLOW_OP_THRESHOLD_MICROS.store(threshold.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) struct Op {
+        name: &'static str,
+        span: EnteredSpan,
+        start: Instant,
+    }
+
+    impl Op {
+        pub(crate) fn new(name: &'static str, key_size: usize, value_size: usize) -> Self {
+            let span = tracing::debug_span!(
+                "rocksdb",
+                op = name,
+                key_size,
+                value_size,
+                duration_us = Empty,
+            );
+            Self {
+                name,
+                span: span.entered(),
+                start: Instant::now(),
+            }
+        }
+
+        pub(crate) fn value_size(&self, value_size: usize) {
+            self.span.record("value_size", value_size);
+        }
+    }
+
+    impl Drop for Op {
+        fn drop(&mut self) {
+            let duration_us = self.start.elapsed().as_micros() as u64;
+            self.span.record("duration_us", duration_us);
+            if duration_us >= SLOW_OP_THRESHOLD_MICROS.load(Ordering::Relaxed) {
+                tracing::warn!(op = self.name, duration_us, "slow rocksdb operation");
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+mod imp {
+    pub(crate) struct Op;
+
+    impl Op {
+        #[inline(always)]
+        pub(crate) fn new(_name: &'static str, _key_size: usize, _value_size: usize) -> Self {
+            Op
+        }
+
+        #[inline(always)]
+        pub(crate) fn value_size(&self, _value_size: usize) {}
+    }
+}
+
+#[cfg(feature = "tracing")]
+pub use imp::set_slow_op_threshold;
+pub(crate) use imp::Op;
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use std::collections::HashMap;
+    use std::fmt::Debug;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata};
+
+    use crate::options::tests::DBPath;
+    use crate::{set_slow_op_threshold, Options, DB};
+
+    type Fields = HashMap<&'static str, String>;
+
+    struct Recorder<'a>(&'a mut Fields);
+
+    impl Visit for Recorder<'_> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0.insert(field.name(), format!("{:?}", value));
+        }
+    }
+
+    // records the fields of every span and event, span ids are indexes + 1
+    #[derive(Default)]
+    struct Collected {
+        spans: Mutex<Vec<Fields>>,
+        events: Mutex<Vec<Fields>>,
+    }
+
+    struct Subscriber(Arc<Collected>);
+
+    impl tracing::Subscriber for Subscriber {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut fields = Fields::new();
+            span.record(&mut Recorder(&mut fields));
+            let mut spans = self.0.spans.lock().unwrap();
+            spans.push(fields);
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            let mut spans = self.0.spans.lock().unwrap();
+            values.record(&mut Recorder(&mut spans[span.into_u64() as usize - 1]));
+        }
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields::new();
+            event.record(&mut Recorder(&mut fields));
+            self.0.events.lock().unwrap().push(fields);
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    #[test]
+    fn test_slow_op_threshold() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+
+        let collected = Arc::new(Collected::default());
+        tracing::subscriber::with_default(Subscriber(collected.clone()), || {
+            // every operation is reported as slow
+            set_slow_op_threshold(Duration::ZERO);
+            db.put_default(b"k", b"value").unwrap();
+            assert_eq!(db.get_default(b"k").unwrap().unwrap(), b"value");
+            set_slow_op_threshold(Duration::from_secs(3600));
+            db.put_default(b"k", b"value").unwrap();
+        });
+
+        let spans = collected.spans.lock().unwrap();
+        let ops: Vec<_> = spans.iter().map(|span| span["op"].as_str()).collect();
+        assert_eq!(ops, ["put", "get", "put"]);
+        for span in spans.iter() {
+            assert_eq!(span["key_size"], "1");
+            // get records the size once it has the value
+            assert_eq!(span["value_size"], "5");
+            assert!(span["duration_us"].parse::<u64>().is_ok());
+        }
+
+        // only the operations before the threshold was raised are slow
+        let events = collected.events.lock().unwrap();
+        let ops: Vec<_> = events.iter().map(|event| event["op"].as_str()).collect();
+        assert_eq!(ops, ["put", "get"]);
+        for event in events.iter() {
+            assert_eq!(event["message"], "slow rocksdb operation");
+            assert!(event["duration_us"].parse::<u64>().is_ok());
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use std::cell::Cell;
 use std::fmt::{Debug, Display, Formatter};
 use std::marker::PhantomData;
 use std::mem::forget;
@@ -6,10 +7,12 @@ use std::ptr::null_mut;
 use librocksdb_sys::*;
 
 use crate::snapshot::BorrowedSnapshot;
-use crate::{Bytes, Error, ReadOptions, Result, TransactionDB};
+use crate::trace::Op;
+use crate::{Bytes, Direction, Error, Iter, KeyRange, ReadOptions, Result, TransactionDB};
 
 pub struct Transaction<'a> {
     inner: *mut rocksdb_transaction_t,
+    savepoints: Cell<usize>,
     _marker: PhantomData<&'a TransactionDB>,
 }
 
@@ -17,19 +20,47 @@ impl<'a> Transaction<'a> {
     pub(crate) fn new(inner: *mut rocksdb_transaction_t) -> Self {
         Self {
             inner,
+            savepoints: Cell::new(0),
             _marker: PhantomData,
         }
     }
 
     pub fn set_savepoint(&self) {
         unsafe { rocksdb_transaction_set_savepoint(self.inner) }
+        self.savepoints.set(self.savepoints.get() + 1);
     }
 
     pub fn rollback_to_savepoint(&self) -> Result<()> {
-        Ok(ffi!(rocksdb_transaction_rollback_to_savepoint(self.inner)))
+        ffi!(rocksdb_transaction_rollback_to_savepoint(self.inner));
+        self.savepoints.set(self.savepoints.get().saturating_sub(1));
+        Ok(())
+    }
+
+    pub fn savepoint(&self) -> Savepoint<'_, 'a> {
+        self.set_savepoint();
+        Savepoint {
+            txn: self,
+            depth: self.savepoints.get(),
+        }
+    }
+
+    // Runs `f` inside a savepoint, rolling back its writes if it returns an error.
+    pub fn scoped<T, E: From<Error>>(
+        &self,
+        f: impl FnOnce(&Self) -> std::result::Result<T, E>,
+    ) -> std::result::Result<T, E> {
+        let savepoint = self.savepoint();
+        match f(self) {
+            Ok(v) => Ok(v),
+            Err(e) => {
+                savepoint.rollback()?;
+                Err(e)
+            }
+        }
     }
 
     pub fn commit(self) -> std::result::Result<OldTransaction<'a>, TransactionError<'a>> {
+        let _op = Op::new("commit", 0, 0);
         let mut errptr = null_mut();
         unsafe { rocksdb_transaction_commit(self.inner, &mut errptr) };
         if errptr.is_null() {
@@ -66,6 +97,7 @@ impl<'a> Transaction<'a> {
 
     pub fn get(&self, read_options: &ReadOptions, key: impl AsRef<[u8]>) -> Result<Option<Bytes>> {
         let key = key.as_ref();
+        let op = Op::new("get", key.len(), 0);
         let mut len = 0;
         let value = ffi!(rocksdb_transaction_get(
             self.inner,
@@ -75,6 +107,7 @@ impl<'a> Transaction<'a> {
             &mut len
         ));
         if !value.is_null() {
+            op.value_size(len);
             Ok(Some(Bytes::new(value, len)))
         } else {
             Ok(None)
@@ -88,6 +121,7 @@ impl<'a> Transaction<'a> {
         exclusive: bool,
     ) -> Result<Option<Bytes>> {
         let key = key.as_ref();
+        let op = Op::new("get_for_update", key.len(), 0);
         let mut len = 0;
         let value = ffi!(rocksdb_transaction_get_for_update(
             self.inner,
@@ -98,6 +132,7 @@ impl<'a> Transaction<'a> {
             exclusive as _
         ));
         if !value.is_null() {
+            op.value_size(len);
             Ok(Some(Bytes::new(value, len)))
         } else {
             Ok(None)
@@ -107,6 +142,7 @@ impl<'a> Transaction<'a> {
     pub fn put(&self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) -> Result<()> {
         let key = key.as_ref();
         let value = value.as_ref();
+        let _op = Op::new("put", key.len(), value.len());
         Ok(ffi!(rocksdb_transaction_put(
             self.inner,
             key.as_ptr() as _,
@@ -116,8 +152,22 @@ impl<'a> Transaction<'a> {
         )))
     }
 
+    pub fn merge(&self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) -> Result<()> {
+        let key = key.as_ref();
+        let value = value.as_ref();
+        let _op = Op::new("merge", key.len(), value.len());
+        Ok(ffi!(rocksdb_transaction_merge(
+            self.inner,
+            key.as_ptr() as _,
+            key.len(),
+            value.as_ptr() as _,
+            value.len()
+        )))
+    }
+
     pub fn delete(&self, key: impl AsRef<[u8]>) -> Result<()> {
         let key = key.as_ref();
+        let _op = Op::new("delete", key.len(), 0);
         Ok(ffi!(rocksdb_transaction_delete(
             self.inner,
             key.as_ptr() as _,
@@ -126,10 +176,27 @@ impl<'a> Transaction<'a> {
     }
 
     pub fn create_iterator(&self, options: &ReadOptions) -> crate::Iterator {
+        let _op = Op::new("create_iterator", 0, 0);
         crate::Iterator::new(unsafe {
             rocksdb_transaction_create_iterator(self.inner, options.inner)
         })
     }
+
+    pub fn iter(&self, options: &ReadOptions) -> Iter<'_> {
+        let mut iter = self.create_iterator(options);
+        iter.seek_to_first();
+        iter.into_iter()
+    }
+
+    pub fn iter_rev(&self, options: &ReadOptions) -> Iter<'_> {
+        let mut iter = self.create_iterator(options);
+        iter.seek_to_last();
+        iter.into_iter_with(Direction::Reverse)
+    }
+
+    pub fn range<'b>(&'b self, options: ReadOptions<'b>, range: impl KeyRange) -> Iter<'b> {
+        Iter::range(options, range, |options| self.create_iterator(options))
+    }
 }
 
 impl<'a> Drop for Transaction<'a> {
@@ -140,6 +207,24 @@ impl<'a> Drop for Transaction<'a> {
 
 unsafe impl<'a> Send for Transaction<'a> {}
 
+// A savepoint that is dropped without a rollback stays on the transaction's
+// stack until the transaction ends, since the C API cannot pop one. That is
+// harmless: rolling back an outer savepoint unwinds every savepoint set after
+// it as well, and a later savepoint only rolls back to itself.
+pub struct Savepoint<'t, 'a> {
+    txn: &'t Transaction<'a>,
+    depth: usize,
+}
+
+impl<'t, 'a> Savepoint<'t, 'a> {
+    pub fn rollback(self) -> Result<()> {
+        while self.txn.savepoints.get() >= self.depth {
+            self.txn.rollback_to_savepoint()?;
+        }
+        Ok(())
+    }
+}
+
 pub struct OldTransaction<'a>(Transaction<'a>);
 
 impl<'a> OldTransaction<'a> {
@@ -189,7 +274,7 @@ impl<'a> std::error::Error for TransactionError<'a> {
 mod tests {
     use crate::options::tests::DBPath;
     use crate::transaction_db::tests::open_new_db;
-    use crate::{ReadOptions, TransactionOptions, WriteOptions};
+    use crate::{Error, ReadOptions, TransactionOptions, WriteOptions};
 
     #[test]
     fn test_get_put_delete() {
@@ -230,4 +315,95 @@ mod tests {
         assert!(txn.commit().is_ok());
         assert_eq!(db.get(&read_op, "foo").unwrap().unwrap().as_ref(), b"bar");
     }
+
+    #[test]
+    fn test_savepoint() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+
+        let write_op = WriteOptions::new();
+        let txn_op = TransactionOptions::new();
+        let txn = db.begin(&write_op, &txn_op, None);
+        let read_op = ReadOptions::new();
+
+        let outer = txn.savepoint();
+        txn.put("foo", "bar").unwrap();
+        // never rolled back
+        let _inner = txn.savepoint();
+        txn.put("bar", "baz").unwrap();
+        txn.put("baz", "qux").unwrap();
+
+        assert!(outer.rollback().is_ok());
+        assert!(txn.get(&read_op, "foo").unwrap().is_none());
+        assert!(txn.get(&read_op, "bar").unwrap().is_none());
+        assert!(txn.get(&read_op, "baz").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_nested_savepoints() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+
+        let write_op = WriteOptions::new();
+        let txn_op = TransactionOptions::new();
+        let txn = db.begin(&write_op, &txn_op, None);
+        let read_op = ReadOptions::new();
+
+        let outer = txn.savepoint();
+        txn.put("a", "1").unwrap();
+        let inner = txn.savepoint();
+        txn.put("b", "2").unwrap();
+        assert!(inner.rollback().is_ok());
+        assert!(txn.get(&read_op, "a").unwrap().is_some());
+        assert!(txn.get(&read_op, "b").unwrap().is_none());
+
+        // a dropped savepoint doesn't change where a later one rolls back to
+        let _ = txn.savepoint();
+        txn.put("c", "3").unwrap();
+        let last = txn.savepoint();
+        txn.put("d", "4").unwrap();
+        assert!(last.rollback().is_ok());
+        assert!(txn.get(&read_op, "c").unwrap().is_some());
+        assert!(txn.get(&read_op, "d").unwrap().is_none());
+
+        assert!(outer.rollback().is_ok());
+        assert!(txn.get(&read_op, "a").unwrap().is_none());
+        assert!(txn.get(&read_op, "c").unwrap().is_none());
+    }
+
+    #[derive(Debug)]
+    enum ScopeError {
+        Aborted,
+        Db,
+    }
+
+    impl From<Error> for ScopeError {
+        fn from(_: Error) -> Self {
+            ScopeError::Db
+        }
+    }
+
+    #[test]
+    fn test_scoped() {
+        let path = DBPath::new();
+        let db = open_new_db(path.as_ref());
+
+        let write_op = WriteOptions::new();
+        let txn_op = TransactionOptions::new();
+        let txn = db.begin(&write_op, &txn_op, None);
+        let read_op = ReadOptions::new();
+
+        let ret = txn.scoped(|txn| {
+            txn.put("foo", "bar")?;
+            let ret = txn.scoped(|txn| {
+                txn.put("bar", "baz")?;
+                Err::<(), _>(ScopeError::Aborted)
+            });
+            assert!(matches!(ret, Err(ScopeError::Aborted)));
+            Ok::<_, ScopeError>(())
+        });
+        assert!(ret.is_ok());
+        assert_eq!(txn.get(&read_op, "foo").unwrap().unwrap().as_ref(), b"bar");
+        assert!(txn.get(&read_op, "bar").unwrap().is_none());
+    }
 }
@@ -1,30 +1,44 @@
-use std::ffi::CString;
+use std::path::Path;
 use std::ptr::null_mut;
+use std::sync::Arc;
 
 use librocksdb_sys::*;
 
-use crate::snapshot::{OwnedSnapshot, ReleaseSnapshot};
+use crate::options::{default_read_options, default_write_options};
+use crate::path_to_cstring;
+use crate::registry::{self, Registration};
+use crate::snapshot::{OwnedSnapshot, ReleaseSnapshot, SharedSnapshot};
+use crate::trace::Op;
 use crate::{
-    Bytes, OldTransaction, Options, ReadOptions, Result, Transaction, WriteBatch, WriteOptions,
+    Bytes, Direction, Iter, KeyRange, OldTransaction, Options, ReadOptions, Result, RetryPolicy,
+    SharedIterator, Transaction, WriteBatch, WriteOptions,
 };
 
 pub struct TransactionDB {
     inner: *mut rocksdb_transactiondb_t,
+    _registration: Registration,
 }
 
 impl TransactionDB {
+    // Fails with ErrorKind::Busy if the path is already open in this process.
     pub fn open(
         options: &Options,
         txn_db_options: &TransactionDBOptions,
-        name: &str,
+        path: impl AsRef<Path>,
     ) -> Result<Self> {
-        let name = CString::new(name).unwrap();
-        let inner = ffi!(rocksdb_transactiondb_open(
-            options.inner,
-            txn_db_options.inner,
-            name.as_ptr()
-        ));
-        Ok(Self { inner })
+        let path = path.as_ref();
+        let (inner, registration) = registry::register(path, || {
+            let path = path_to_cstring(path)?;
+            Ok(ffi!(rocksdb_transactiondb_open(
+                options.inner,
+                txn_db_options.inner,
+                path.as_ptr()
+            )))
+        })?;
+        Ok(Self {
+            inner,
+            _registration: registration,
+        })
     }
 
     pub fn create_snapshot(&self) -> OwnedSnapshot<'_, Self> {
@@ -33,9 +47,19 @@ impl TransactionDB {
         OwnedSnapshot { inner, db: self }
     }
 
+    pub fn create_shared_snapshot(self: &Arc<Self>) -> SharedSnapshot<Self> {
+        let inner = unsafe { rocksdb_transactiondb_create_snapshot(self.inner) };
+        debug_assert!(!inner.is_null());
+        SharedSnapshot {
+            inner,
+            db: self.clone(),
+        }
+    }
+
     pub fn get(&self, options: &ReadOptions, key: impl AsRef<[u8]>) -> Result<Option<Bytes>> {
         let mut len: usize = 0;
         let key = key.as_ref();
+        let op = Op::new("get", key.len(), 0);
         let value = ffi!(rocksdb_transactiondb_get(
             self.inner,
             options.inner,
@@ -44,6 +68,7 @@ impl TransactionDB {
             &mut len
         ));
         if !value.is_null() {
+            op.value_size(len);
             Ok(Some(Bytes::new(value, len)))
         } else {
             Ok(None)
@@ -58,6 +83,7 @@ impl TransactionDB {
     ) -> Result<()> {
         let key = key.as_ref();
         let value = value.as_ref();
+        let _op = Op::new("put", key.len(), value.len());
         Ok(ffi!(rocksdb_transactiondb_put(
             self.inner,
             options.inner,
@@ -68,7 +94,28 @@ impl TransactionDB {
         )))
     }
 
+    // needs a merge operator set on the options
+    pub fn merge(
+        &self,
+        options: &WriteOptions,
+        key: impl AsRef<[u8]>,
+        value: impl AsRef<[u8]>,
+    ) -> Result<()> {
+        let key = key.as_ref();
+        let value = value.as_ref();
+        let _op = Op::new("merge", key.len(), value.len());
+        Ok(ffi!(rocksdb_transactiondb_merge(
+            self.inner,
+            options.inner,
+            key.as_ptr() as _,
+            key.len(),
+            value.as_ptr() as _,
+            value.len()
+        )))
+    }
+
     pub fn write(&self, options: &WriteOptions, batch: &WriteBatch) -> Result<()> {
+        let _op = Op::new("write", 0, 0);
         Ok(ffi!(rocksdb_transactiondb_write(
             self.inner,
             options.inner,
@@ -78,6 +125,7 @@ impl TransactionDB {
 
     pub fn delete(&self, options: &WriteOptions, key: impl AsRef<[u8]>) -> Result<()> {
         let key = key.as_ref();
+        let _op = Op::new("delete", key.len(), 0);
         Ok(ffi!(rocksdb_transactiondb_delete(
             self.inner,
             options.inner,
@@ -87,11 +135,36 @@ impl TransactionDB {
     }
 
     pub fn create_iterator(&self, options: &ReadOptions) -> crate::Iterator {
+        let _op = Op::new("create_iterator", 0, 0);
         crate::Iterator::new(unsafe {
             rocksdb_transactiondb_create_iterator(self.inner, options.inner)
         })
     }
 
+    pub fn iter(&self, options: &ReadOptions) -> Iter<'_> {
+        let mut iter = self.create_iterator(options);
+        iter.seek_to_first();
+        iter.into_iter()
+    }
+
+    pub fn iter_rev(&self, options: &ReadOptions) -> Iter<'_> {
+        let mut iter = self.create_iterator(options);
+        iter.seek_to_last();
+        iter.into_iter_with(Direction::Reverse)
+    }
+
+    pub fn range<'b>(&'b self, options: ReadOptions<'b>, range: impl KeyRange) -> Iter<'b> {
+        Iter::range(options, range, |options| self.create_iterator(options))
+    }
+
+    pub fn create_shared_iterator(
+        self: &Arc<Self>,
+        options: ReadOptions<'static>,
+    ) -> SharedIterator<Self> {
+        let inner = unsafe { rocksdb_transactiondb_create_iterator(self.inner, options.inner) };
+        SharedIterator::new(inner, options, self.clone())
+    }
+
     pub fn begin<'a>(
         &self,
         write_options: &WriteOptions,
@@ -107,6 +180,56 @@ impl TransactionDB {
         };
         Transaction::new(inner)
     }
+
+    // Runs `f` in a transaction and commits it. The whole transaction,
+    // including `f`, is retried according to `policy`.
+    pub fn transaction<T>(
+        &self,
+        policy: &RetryPolicy,
+        mut f: impl FnMut(&Transaction) -> Result<T>,
+    ) -> Result<T> {
+        let txn_options = TransactionOptions::new();
+        policy.run(|| {
+            let txn = self.begin(default_write_options(), &txn_options, None);
+            let value = f(&txn)?;
+            txn.commit()?;
+            Ok(value)
+        })
+    }
+
+    // Read-modify-write of a single key. `f` gets the current value and returns
+    // the new one, None deletes the key. `f` may be called more than once, the
+    // whole update is retried with the default RetryPolicy.
+    pub fn update(
+        &self,
+        key: impl AsRef<[u8]>,
+        mut f: impl FnMut(Option<&[u8]>) -> Option<Vec<u8>>,
+    ) -> Result<()> {
+        let key = key.as_ref();
+        self.transaction(&RetryPolicy::default(), |txn| {
+            let old = txn.get_for_update(default_read_options(), key, true)?;
+            match f(old.as_deref()) {
+                Some(value) => txn.put(key, value),
+                None => txn.delete(key),
+            }
+        })
+    }
+
+    pub fn create_iterator_default(&self) -> crate::Iterator<'_> {
+        self.create_iterator(default_read_options())
+    }
+
+    pub fn get_default(&self, key: impl AsRef<[u8]>) -> Result<Option<Bytes>> {
+        self.get(default_read_options(), key)
+    }
+
+    pub fn put_default(&self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) -> Result<()> {
+        self.put(default_write_options(), key, value)
+    }
+
+    pub fn delete_default(&self, key: impl AsRef<[u8]>) -> Result<()> {
+        self.delete(default_write_options(), key)
+    }
 }
 
 impl ReleaseSnapshot for TransactionDB {
@@ -121,6 +244,10 @@ impl Drop for TransactionDB {
     }
 }
 
+unsafe impl Send for TransactionDB {}
+
+unsafe impl Sync for TransactionDB {}
+
 define!(
     TransactionDBOptions,
     rocksdb_transactiondb_options_t,
@@ -129,24 +256,28 @@ define!(
 );
 
 impl TransactionDBOptions {
-    pub fn set_max_num_locks(&mut self, max_num_locks: i64) {
-        unsafe { rocksdb_transactiondb_options_set_max_num_locks(self.inner, max_num_locks) }
+    pub fn set_max_num_locks(&mut self, max_num_locks: i64) -> &mut Self {
+        unsafe { rocksdb_transactiondb_options_set_max_num_locks(self.inner, max_num_locks) };
+        self
     }
 
-    pub fn set_num_stripes(&mut self, num_stripes: usize) {
-        unsafe { rocksdb_transactiondb_options_set_num_stripes(self.inner, num_stripes) }
+    pub fn set_num_stripes(&mut self, num_stripes: usize) -> &mut Self {
+        unsafe { rocksdb_transactiondb_options_set_num_stripes(self.inner, num_stripes) };
+        self
     }
 
-    pub fn set_transaction_lock_timeout(&mut self, txn_lock_timeout: i64) {
+    pub fn set_transaction_lock_timeout(&mut self, txn_lock_timeout: i64) -> &mut Self {
         unsafe {
             rocksdb_transactiondb_options_set_transaction_lock_timeout(self.inner, txn_lock_timeout)
-        }
+        };
+        self
     }
 
-    pub fn set_default_lock_timeout(&mut self, default_lock_timeout: i64) {
+    pub fn set_default_lock_timeout(&mut self, default_lock_timeout: i64) -> &mut Self {
         unsafe {
             rocksdb_transactiondb_options_set_default_lock_timeout(self.inner, default_lock_timeout)
-        }
+        };
+        self
     }
 }
 
@@ -158,33 +289,44 @@ define!(
 );
 
 impl TransactionOptions {
-    pub fn set_set_snapshot(&mut self, set_snapshot: bool) {
-        unsafe { rocksdb_transaction_options_set_set_snapshot(self.inner, set_snapshot as _) }
+    pub fn set_set_snapshot(&mut self, set_snapshot: bool) -> &mut Self {
+        unsafe { rocksdb_transaction_options_set_set_snapshot(self.inner, set_snapshot as _) };
+        self
     }
 
-    pub fn set_deadlock_detect(&mut self, deadlock_detect: bool) {
-        unsafe { rocksdb_transaction_options_set_deadlock_detect(self.inner, deadlock_detect as _) }
+    pub fn set_deadlock_detect(&mut self, deadlock_detect: bool) -> &mut Self {
+        unsafe {
+            rocksdb_transaction_options_set_deadlock_detect(self.inner, deadlock_detect as _)
+        };
+        self
     }
 
-    pub fn set_lock_timeout(&mut self, lock_timeout: i64) {
-        unsafe { rocksdb_transaction_options_set_lock_timeout(self.inner, lock_timeout) }
+    pub fn set_lock_timeout(&mut self, lock_timeout: i64) -> &mut Self {
+        unsafe { rocksdb_transaction_options_set_lock_timeout(self.inner, lock_timeout) };
+        self
     }
 
-    pub fn set_expiration(&mut self, expiration: i64) {
-        unsafe { rocksdb_transaction_options_set_expiration(self.inner, expiration) }
+    pub fn set_expiration(&mut self, expiration: i64) -> &mut Self {
+        unsafe { rocksdb_transaction_options_set_expiration(self.inner, expiration) };
+        self
     }
 
-    pub fn set_deadlock_detect_depth(&mut self, depth: i64) {
-        unsafe { rocksdb_transaction_options_set_deadlock_detect_depth(self.inner, depth) }
+    pub fn set_deadlock_detect_depth(&mut self, depth: i64) -> &mut Self {
+        unsafe { rocksdb_transaction_options_set_deadlock_detect_depth(self.inner, depth) };
+        self
     }
 
-    pub fn set_max_write_batch_size(&mut self, size: usize) {
-        unsafe { rocksdb_transaction_options_set_max_write_batch_size(self.inner, size) }
+    pub fn set_max_write_batch_size(&mut self, size: usize) -> &mut Self {
+        unsafe { rocksdb_transaction_options_set_max_write_batch_size(self.inner, size) };
+        self
     }
 }
 
 #[cfg(test)]
 pub(crate) mod tests {
+    use std::sync::Arc;
+    use std::thread;
+
     use crate::options::tests::DBPath;
     use crate::snapshot::NullSnapshot;
     use crate::{
@@ -323,4 +465,39 @@ pub(crate) mod tests {
         iter.seek_for_prev("foo3");
         assert!(iter.valid());
     }
+
+    #[test]
+    fn test_update() {
+        let path = DBPath::new();
+        let db = Arc::new(open_new_db(path.as_ref()));
+
+        db.update("foo", |old| {
+            assert!(old.is_none());
+            Some(b"bar".to_vec())
+        })
+        .unwrap();
+        assert_eq!(db.get_default("foo").unwrap().unwrap(), b"bar");
+        db.update("foo", |_| None).unwrap();
+        assert!(db.get_default("foo").unwrap().is_none());
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let db = db.clone();
+                thread::spawn(move || {
+                    for _ in 0..50 {
+                        db.update("counter", |old| {
+                            let n = old.map_or(0, |v| u64::from_be_bytes(v.try_into().unwrap()));
+                            Some((n + 1).to_be_bytes().to_vec())
+                        })
+                        .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+        let counter = db.get_default("counter").unwrap().unwrap();
+        assert_eq!(u64::from_be_bytes((&*counter).try_into().unwrap()), 200);
+    }
 }
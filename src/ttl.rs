@@ -0,0 +1,252 @@
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::options::{default_read_options, default_write_options};
+use crate::{CompactionDecision, CompactionFilter, Error, Iter, Options, Result, DB};
+
+// Every value is prefixed with its expiry time as big-endian unix seconds,
+// 0 means the value never expires.
+const HEADER_LEN: usize = 8;
+const NO_EXPIRY: u64 = 0;
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn is_expired(expiry: u64, now: u64) -> bool {
+    expiry != NO_EXPIRY && expiry <= now
+}
+
+fn decode(value: &[u8]) -> Result<(u64, &[u8])> {
+    if value.len() < HEADER_LEN {
+        return Err(Error::corruption("value is missing the ttl header"));
+    }
+    let (header, value) = value.split_at(HEADER_LEN);
+    Ok((u64::from_be_bytes(header.try_into().unwrap()), value))
+}
+
+// Drops expired values, then hands the rest to the user's filter, if any,
+// without their header.
+struct TtlFilter {
+    inner: Option<Box<dyn CompactionFilter>>,
+}
+
+impl CompactionFilter for TtlFilter {
+    fn name(&self) -> &str {
+        "rocksdb.TtlCompactionFilter"
+    }
+
+    fn filter(&self, level: u32, key: &[u8], value: &[u8]) -> CompactionDecision {
+        let (expiry, value) = match decode(value) {
+            Ok((expiry, _)) if is_expired(expiry, now()) => return CompactionDecision::Remove,
+            Ok(decoded) => decoded,
+            // not written through TtlDb
+            Err(_) => return CompactionDecision::Keep,
+        };
+        let inner = match &self.inner {
+            Some(inner) => inner,
+            None => return CompactionDecision::Keep,
+        };
+        match inner.filter(level, key, value) {
+            // the new value keeps the old expiry
+            CompactionDecision::Change(value) => {
+                let mut buf = Vec::with_capacity(HEADER_LEN + value.len());
+                buf.extend_from_slice(&expiry.to_be_bytes());
+                buf.extend_from_slice(&value);
+                CompactionDecision::Change(buf)
+            }
+            decision => decision,
+        }
+    }
+}
+
+// Expired values are hidden on reads and dropped by compaction.
+pub struct TtlDb {
+    db: DB,
+}
+
+impl TtlDb {
+    // Expiry is enforced by a compaction filter, which replaces any filter set
+    // on `options`. Use open_with_filter to keep one.
+    pub fn open(options: &Options, path: impl AsRef<Path>) -> Result<Self> {
+        Self::open_inner(options, path, None)
+    }
+
+    // `filter` runs after the expiry check and only sees unexpired values,
+    // without the ttl header.
+    pub fn open_with_filter(
+        options: &Options,
+        path: impl AsRef<Path>,
+        filter: impl CompactionFilter,
+    ) -> Result<Self> {
+        Self::open_inner(options, path, Some(Box::new(filter)))
+    }
+
+    fn open_inner(
+        options: &Options,
+        path: impl AsRef<Path>,
+        inner: Option<Box<dyn CompactionFilter>>,
+    ) -> Result<Self> {
+        let mut options = options.clone();
+        options.set_compaction_filter(TtlFilter { inner });
+        Ok(Self {
+            db: DB::open(&options, path)?,
+        })
+    }
+
+    pub fn get(&self, key: impl AsRef<[u8]>) -> Result<Option<Vec<u8>>> {
+        match self.db.get(default_read_options(), key)? {
+            Some(value) => {
+                let (expiry, value) = decode(&value)?;
+                if is_expired(expiry, now()) {
+                    Ok(None)
+                } else {
+                    Ok(Some(value.to_vec()))
+                }
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn put(&self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) -> Result<()> {
+        self.put_with_expiry(key, value, NO_EXPIRY)
+    }
+
+    pub fn put_with_ttl(
+        &self,
+        key: impl AsRef<[u8]>,
+        value: impl AsRef<[u8]>,
+        ttl: Duration,
+    ) -> Result<()> {
+        // never 0, which would mean no expiry
+        let expiry = now().saturating_add(ttl.as_secs()).max(1);
+        self.put_with_expiry(key, value, expiry)
+    }
+
+    fn put_with_expiry(
+        &self,
+        key: impl AsRef<[u8]>,
+        value: impl AsRef<[u8]>,
+        expiry: u64,
+    ) -> Result<()> {
+        let value = value.as_ref();
+        let mut buf = Vec::with_capacity(HEADER_LEN + value.len());
+        buf.extend_from_slice(&expiry.to_be_bytes());
+        buf.extend_from_slice(value);
+        self.db.put(default_write_options(), key, buf)
+    }
+
+    pub fn delete(&self, key: impl AsRef<[u8]>) -> Result<()> {
+        self.db.delete(default_write_options(), key)
+    }
+
+    pub fn iter(&self) -> TtlIter<'_> {
+        TtlIter {
+            iter: self.db.iter(default_read_options()),
+            now: now(),
+        }
+    }
+}
+
+pub struct TtlIter<'a> {
+    iter: Iter<'a>,
+    now: u64,
+}
+
+impl<'a> Iterator for TtlIter<'a> {
+    type Item = Result<(Box<[u8]>, Box<[u8]>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (key, value) = match self.iter.next()? {
+                Ok(item) => item,
+                Err(e) => return Some(Err(e)),
+            };
+            match decode(&value) {
+                Ok((expiry, _)) if is_expired(expiry, self.now) => continue,
+                Ok((_, value)) => return Some(Ok((key, value.into()))),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::options::default_read_options;
+    use crate::options::tests::DBPath;
+    use crate::{CompactionDecision, CompactionFilter, Options, TtlDb};
+
+    #[test]
+    fn test_ttl() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = TtlDb::open(&options, path.as_ref()).unwrap();
+
+        db.put("a", "1").unwrap();
+        db.put_with_ttl("b", "2", Duration::ZERO).unwrap();
+        db.put_with_ttl("c", "3", Duration::from_secs(3600))
+            .unwrap();
+
+        assert_eq!(db.get("a").unwrap().unwrap(), b"1");
+        assert!(db.get("b").unwrap().is_none());
+        assert_eq!(db.get("c").unwrap().unwrap(), b"3");
+
+        let items: Vec<_> = db.iter().map(|item| item.unwrap()).collect();
+        assert_eq!(items.len(), 2);
+        assert_eq!(&*items[0].0, b"a");
+        assert_eq!(&*items[0].1, b"1");
+        assert_eq!(&*items[1].0, b"c");
+        assert_eq!(&*items[1].1, b"3");
+
+        db.delete("a").unwrap();
+        assert!(db.get("a").unwrap().is_none());
+    }
+
+    struct Filter;
+
+    impl CompactionFilter for Filter {
+        fn name(&self) -> &str {
+            "filter"
+        }
+
+        fn filter(&self, _level: u32, key: &[u8], value: &[u8]) -> CompactionDecision {
+            match key {
+                b"drop" => CompactionDecision::Remove,
+                b"change" => CompactionDecision::Change([value, b"!"].concat()),
+                _ => CompactionDecision::Keep,
+            }
+        }
+    }
+
+    #[test]
+    fn test_ttl_with_filter() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = TtlDb::open_with_filter(&options, path.as_ref(), Filter).unwrap();
+
+        db.put("drop", "1").unwrap();
+        db.put_with_ttl("change", "2", Duration::from_secs(3600))
+            .unwrap();
+        db.put_with_ttl("expired", "3", Duration::ZERO).unwrap();
+        db.put("keep", "4").unwrap();
+        db.db.compact_range(None, None);
+
+        assert!(db.get("drop").unwrap().is_none());
+        assert_eq!(db.get("change").unwrap().unwrap(), b"2!");
+        assert_eq!(db.get("keep").unwrap().unwrap(), b"4");
+        // removed by the expiry check, not just hidden
+        let raw: Vec<_> = db
+            .db
+            .iter(default_read_options())
+            .map(|item| item.unwrap().0)
+            .collect();
+        assert_eq!(raw, [&b"change"[..], b"keep"].map(Box::from));
+    }
+}
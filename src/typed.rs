@@ -0,0 +1,242 @@
+use std::marker::PhantomData;
+
+use crate::options::{default_read_options, default_write_options};
+use crate::{ColumnFamily, Error, Iter, Result, DB};
+
+pub trait Codec<T> {
+    fn encode(value: &T) -> Result<Vec<u8>>;
+
+    fn decode(bytes: &[u8]) -> Result<T>;
+}
+
+pub struct BytesCodec;
+
+impl Codec<Vec<u8>> for BytesCodec {
+    fn encode(value: &Vec<u8>) -> Result<Vec<u8>> {
+        Ok(value.clone())
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Vec<u8>> {
+        Ok(bytes.to_vec())
+    }
+}
+
+pub struct StringCodec;
+
+impl Codec<String> for StringCodec {
+    fn encode(value: &String) -> Result<Vec<u8>> {
+        Ok(value.as_bytes().to_vec())
+    }
+
+    fn decode(bytes: &[u8]) -> Result<String> {
+        String::from_utf8(bytes.to_vec()).map_err(|_| Error::corruption("invalid UTF-8"))
+    }
+}
+
+// Fixed-width big-endian integers, which sort in numeric order.
+pub struct BigEndianCodec;
+
+macro_rules! big_endian_codec {
+    ($($t:ty),*) => {$(
+        impl Codec<$t> for BigEndianCodec {
+            fn encode(value: &$t) -> Result<Vec<u8>> {
+                Ok(value.to_be_bytes().to_vec())
+            }
+
+            fn decode(bytes: &[u8]) -> Result<$t> {
+                match bytes.try_into() {
+                    Ok(bytes) => Ok(<$t>::from_be_bytes(bytes)),
+                    Err(_) => Err(Error::corruption("unexpected integer length")),
+                }
+            }
+        }
+    )*};
+}
+
+big_endian_codec!(u16, u32, u64, u128);
+
+#[cfg(feature = "bincode")]
+pub struct BincodeCodec;
+
+#[cfg(feature = "bincode")]
+impl<T: serde::Serialize + serde::de::DeserializeOwned> Codec<T> for BincodeCodec {
+    fn encode(value: &T) -> Result<Vec<u8>> {
+        bincode::serialize(value).map_err(|e| Error::invalid_argument(&e.to_string()))
+    }
+
+    fn decode(bytes: &[u8]) -> Result<T> {
+        bincode::deserialize(bytes).map_err(|e| Error::corruption(&e.to_string()))
+    }
+}
+
+#[cfg(feature = "bincode")]
+pub type BincodeDb<'a, K, V> = TypedDb<'a, K, V, BincodeCodec, BincodeCodec>;
+
+#[cfg(feature = "bincode")]
+pub type BincodeCf<'a, K, V> = TypedCf<'a, K, V, BincodeCodec, BincodeCodec>;
+
+// fn() keeps the marker Send + Sync regardless of K and V
+type Marker<K, V, KC, VC> = PhantomData<fn() -> (K, V, KC, VC)>;
+
+pub struct TypedDb<'a, K, V, KC, VC> {
+    db: &'a DB,
+    _marker: Marker<K, V, KC, VC>,
+}
+
+impl<'a, K, V, KC: Codec<K>, VC: Codec<V>> TypedDb<'a, K, V, KC, VC> {
+    pub fn new(db: &'a DB) -> Self {
+        Self {
+            db,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Result<Option<V>> {
+        match self.db.get(default_read_options(), KC::encode(key)?)? {
+            Some(value) => Ok(Some(VC::decode(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn put(&self, key: &K, value: &V) -> Result<()> {
+        self.db.put(
+            default_write_options(),
+            KC::encode(key)?,
+            VC::encode(value)?,
+        )
+    }
+
+    pub fn delete(&self, key: &K) -> Result<()> {
+        self.db.delete(default_write_options(), KC::encode(key)?)
+    }
+
+    pub fn iter(&self) -> TypedIter<'a, K, V, KC, VC> {
+        TypedIter {
+            iter: self.db.iter(default_read_options()),
+            _marker: PhantomData,
+        }
+    }
+}
+
+// Like TypedDb, over one column family of the db.
+pub struct TypedCf<'a, K, V, KC, VC> {
+    db: &'a DB,
+    cf: ColumnFamily<'a>,
+    _marker: Marker<K, V, KC, VC>,
+}
+
+impl<'a, K, V, KC: Codec<K>, VC: Codec<V>> TypedCf<'a, K, V, KC, VC> {
+    pub fn new(db: &'a DB, cf: ColumnFamily<'a>) -> Self {
+        Self {
+            db,
+            cf,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Result<Option<V>> {
+        match self
+            .db
+            .get_cf(default_read_options(), &self.cf, KC::encode(key)?)?
+        {
+            Some(value) => Ok(Some(VC::decode(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn put(&self, key: &K, value: &V) -> Result<()> {
+        self.db.put_cf(
+            default_write_options(),
+            &self.cf,
+            KC::encode(key)?,
+            VC::encode(value)?,
+        )
+    }
+
+    pub fn delete(&self, key: &K) -> Result<()> {
+        self.db
+            .delete_cf(default_write_options(), &self.cf, KC::encode(key)?)
+    }
+
+    pub fn iter(&self) -> Result<TypedIter<'a, K, V, KC, VC>> {
+        let mut iter = self
+            .db
+            .create_iterator_cf(default_read_options(), &self.cf)?;
+        iter.seek_to_first();
+        Ok(TypedIter {
+            iter: iter.into_iter(),
+            _marker: PhantomData,
+        })
+    }
+}
+
+pub struct TypedIter<'a, K, V, KC, VC> {
+    iter: Iter<'a>,
+    _marker: Marker<K, V, KC, VC>,
+}
+
+impl<'a, K, V, KC: Codec<K>, VC: Codec<V>> Iterator for TypedIter<'a, K, V, KC, VC> {
+    type Item = Result<(K, V)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(
+            self.iter
+                .next()?
+                .and_then(|(key, value)| Ok((KC::decode(&key)?, VC::decode(&value)?))),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::options::tests::DBPath;
+    use crate::{
+        BigEndianCodec, ErrorKind, Options, StringCodec, TypedCf, TypedDb, WriteOptions, DB,
+    };
+
+    #[test]
+    fn test_typed_db() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+
+        let typed: TypedDb<u64, String, BigEndianCodec, StringCodec> = TypedDb::new(&db);
+        assert!(typed.get(&1).unwrap().is_none());
+        typed.put(&256, &"bar".to_string()).unwrap();
+        typed.put(&1, &"foo".to_string()).unwrap();
+        assert_eq!(typed.get(&1).unwrap().unwrap(), "foo");
+
+        let items: Vec<_> = typed.iter().map(|item| item.unwrap()).collect();
+        assert_eq!(items, [(1, "foo".to_string()), (256, "bar".to_string())]);
+
+        typed.delete(&1).unwrap();
+        assert!(typed.get(&1).unwrap().is_none());
+
+        db.put(&WriteOptions::new(), 2u64.to_be_bytes(), b"\xff")
+            .unwrap();
+        assert_eq!(typed.get(&2).unwrap_err().kind(), ErrorKind::Corruption);
+    }
+
+    #[test]
+    fn test_typed_cf() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+        let cf = db.create_cf("typed", &Options::new()).unwrap();
+
+        let typed: TypedCf<u64, String, BigEndianCodec, StringCodec> = TypedCf::new(&db, cf);
+        typed.put(&2, &"bar".to_string()).unwrap();
+        typed.put(&1, &"foo".to_string()).unwrap();
+        assert_eq!(typed.get(&1).unwrap().unwrap(), "foo");
+        // the default family is untouched
+        assert!(db.get_default(1u64.to_be_bytes()).unwrap().is_none());
+
+        let items: Vec<_> = typed.iter().unwrap().map(|item| item.unwrap()).collect();
+        assert_eq!(items, [(1, "foo".to_string()), (2, "bar".to_string())]);
+
+        typed.delete(&1).unwrap();
+        assert!(typed.get(&1).unwrap().is_none());
+    }
+}
@@ -0,0 +1,109 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::{Error, DB};
+
+// Syncs the WAL of a db in a background thread, for writes made with sync
+// off. At most `interval` worth of acknowledged writes can be lost on a
+// crash, without paying for an fsync per write. The thread stops on drop.
+pub struct WalSyncer {
+    state: Arc<State>,
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+#[derive(Default)]
+struct State {
+    durable_sequence: AtomicU64,
+    last_error: Mutex<Option<Error>>,
+}
+
+impl WalSyncer {
+    pub fn start(db: Arc<DB>, interval: Duration) -> Self {
+        let state = Arc::new(State::default());
+        let (stop, stopped) = mpsc::channel();
+        let thread = thread::spawn({
+            let state = state.clone();
+            move || {
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                    // writes up to this sequence number are already in the
+                    // WAL, the sync makes them durable
+                    let sequence = db.latest_sequence_number();
+                    match db.sync_wal() {
+                        Ok(()) => state.durable_sequence.store(sequence, Ordering::Release),
+                        Err(e) => *state.last_error() = Some(e),
+                    }
+                }
+            }
+        });
+        Self {
+            state,
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+
+    // Writes with a sequence number up to this one survive a crash. Zero
+    // until the first sync.
+    pub fn durable_sequence_number(&self) -> u64 {
+        self.state.durable_sequence.load(Ordering::Acquire)
+    }
+
+    // the error of the most recent failed sync, cleared by this call
+    pub fn take_error(&self) -> Option<Error> {
+        self.state.last_error().take()
+    }
+}
+
+impl State {
+    fn last_error(&self) -> MutexGuard<'_, Option<Error>> {
+        self.last_error
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Drop for WalSyncer {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::options::tests::DBPath;
+    use crate::{Options, WalSyncer, DB};
+
+    #[test]
+    fn test_wal_syncer() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = Arc::new(DB::open(&options, path.as_ref()).unwrap());
+
+        let syncer = WalSyncer::start(db.clone(), Duration::from_millis(10));
+        db.put_default("key", "value").unwrap();
+        let sequence = db.latest_sequence_number();
+        assert!(sequence > 0);
+        for _ in 0..100 {
+            if syncer.durable_sequence_number() >= sequence {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(syncer.durable_sequence_number() >= sequence);
+        assert!(syncer.take_error().is_none());
+        drop(syncer);
+        assert_eq!(Arc::strong_count(&db), 1);
+    }
+}
@@ -18,6 +18,12 @@ impl WriteBatch {
         unsafe { rocksdb_writebatch_count(self.inner) }
     }
 
+    pub fn size_in_bytes(&self) -> usize {
+        let mut size = 0;
+        unsafe { rocksdb_writebatch_data(self.inner, &mut size) };
+        size
+    }
+
     pub fn put(&mut self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) {
         let key = key.as_ref();
         let value = value.as_ref();
@@ -32,8 +38,36 @@ impl WriteBatch {
         }
     }
 
+    pub fn merge(&mut self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) {
+        let key = key.as_ref();
+        let value = value.as_ref();
+        unsafe {
+            rocksdb_writebatch_merge(
+                self.inner,
+                key.as_ptr() as _,
+                key.len(),
+                value.as_ptr() as _,
+                value.len(),
+            )
+        }
+    }
+
     pub fn delete(&mut self, key: impl AsRef<[u8]>) {
         let key = key.as_ref();
         unsafe { rocksdb_writebatch_delete(self.inner, key.as_ptr() as _, key.len()) }
     }
+
+    // deletes [begin, end)
+    pub fn delete_range(&mut self, begin: impl AsRef<[u8]>, end: impl AsRef<[u8]>) {
+        let (begin, end) = (begin.as_ref(), end.as_ref());
+        unsafe {
+            rocksdb_writebatch_delete_range(
+                self.inner,
+                begin.as_ptr() as _,
+                begin.len(),
+                end.as_ptr() as _,
+                end.len(),
+            )
+        }
+    }
 }
@@ -0,0 +1,121 @@
+use std::os::raw::c_int;
+
+use librocksdb_sys::*;
+
+use crate::{Bytes, Options, ReadOptions, Result, DB};
+
+pub struct WriteBatchWithIndex {
+    pub(crate) inner: *mut rocksdb_writebatch_wi_t,
+}
+
+impl WriteBatchWithIndex {
+    pub fn new(reserved_bytes: usize, overwrite_key: bool) -> Self {
+        Self {
+            inner: unsafe { rocksdb_writebatch_wi_create(reserved_bytes, overwrite_key as _) },
+        }
+    }
+
+    pub fn clear(&mut self) {
+        unsafe { rocksdb_writebatch_wi_clear(self.inner) }
+    }
+
+    pub fn count(&self) -> c_int {
+        unsafe { rocksdb_writebatch_wi_count(self.inner) }
+    }
+
+    pub fn put(&mut self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) {
+        let key = key.as_ref();
+        let value = value.as_ref();
+        unsafe {
+            rocksdb_writebatch_wi_put(
+                self.inner,
+                key.as_ptr() as _,
+                key.len(),
+                value.as_ptr() as _,
+                value.len(),
+            )
+        }
+    }
+
+    pub fn merge(&mut self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) {
+        let key = key.as_ref();
+        let value = value.as_ref();
+        unsafe {
+            rocksdb_writebatch_wi_merge(
+                self.inner,
+                key.as_ptr() as _,
+                key.len(),
+                value.as_ptr() as _,
+                value.len(),
+            )
+        }
+    }
+
+    pub fn delete(&mut self, key: impl AsRef<[u8]>) {
+        let key = key.as_ref();
+        unsafe { rocksdb_writebatch_wi_delete(self.inner, key.as_ptr() as _, key.len()) }
+    }
+
+    pub fn get_from_batch(
+        &self,
+        options: &Options,
+        key: impl AsRef<[u8]>,
+    ) -> Result<Option<Bytes>> {
+        let mut len: usize = 0;
+        let key = key.as_ref();
+        let value = ffi!(rocksdb_writebatch_wi_get_from_batch(
+            self.inner,
+            options.inner,
+            key.as_ptr() as _,
+            key.len(),
+            &mut len
+        ));
+        if !value.is_null() {
+            Ok(Some(Bytes::new(value, len)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn get_from_batch_and_db(
+        &self,
+        db: &DB,
+        options: &ReadOptions,
+        key: impl AsRef<[u8]>,
+    ) -> Result<Option<Bytes>> {
+        let mut len: usize = 0;
+        let key = key.as_ref();
+        let value = ffi!(rocksdb_writebatch_wi_get_from_batch_and_db(
+            self.inner,
+            db.inner,
+            options.inner,
+            key.as_ptr() as _,
+            key.len(),
+            &mut len
+        ));
+        if !value.is_null() {
+            Ok(Some(Bytes::new(value, len)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn create_iterator_with_base<'a>(
+        &'a self,
+        base_iterator: crate::Iterator<'a>,
+    ) -> crate::Iterator<'a> {
+        crate::Iterator::new(unsafe {
+            rocksdb_writebatch_wi_create_iterator_with_base(self.inner, base_iterator.into_raw())
+        })
+    }
+}
+
+impl Drop for WriteBatchWithIndex {
+    fn drop(&mut self) {
+        unsafe { rocksdb_writebatch_wi_destroy(self.inner) }
+    }
+}
+
+unsafe impl Send for WriteBatchWithIndex {}
+
+unsafe impl Sync for WriteBatchWithIndex {}
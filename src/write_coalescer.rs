@@ -0,0 +1,171 @@
+use std::mem;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::{Result, WriteBatch, WriteOptions, DB};
+
+// Group commit for writes from many threads. The first writer of a group
+// waits up to max_delay, or until the group reaches max_batch_bytes, then
+// writes everything the other threads added meanwhile as one synced batch.
+// Every writer blocks until its group is written and gets the group's result.
+pub struct WriteCoalescer<'a> {
+    db: &'a DB,
+    options: WriteOptions,
+    max_delay: Duration,
+    max_batch_bytes: usize,
+    pending: Mutex<Pending>,
+    // wakes the leader when the pending batch is full
+    full: Condvar,
+}
+
+struct Pending {
+    batch: WriteBatch,
+    group: Arc<Group>,
+    has_leader: bool,
+}
+
+#[derive(Default)]
+struct Group {
+    result: Mutex<Option<Result<()>>>,
+    done: Condvar,
+}
+
+impl<'a> WriteCoalescer<'a> {
+    pub fn new(db: &'a DB) -> Self {
+        let mut options = WriteOptions::new();
+        options.set_sync(true);
+        Self {
+            db,
+            options,
+            max_delay: Duration::from_millis(1),
+            max_batch_bytes: 1 << 20,
+            pending: Mutex::new(Pending {
+                batch: WriteBatch::new(),
+                group: Default::default(),
+                has_leader: false,
+            }),
+            full: Condvar::new(),
+        }
+    }
+
+    pub fn set_max_delay(&mut self, delay: Duration) -> &mut Self {
+        self.max_delay = delay;
+        self
+    }
+
+    pub fn set_max_batch_bytes(&mut self, bytes: usize) -> &mut Self {
+        self.max_batch_bytes = bytes;
+        self
+    }
+
+    pub fn set_sync(&mut self, sync: bool) -> &mut Self {
+        self.options.set_sync(sync);
+        self
+    }
+
+    pub fn put(&self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) -> Result<()> {
+        self.commit(|batch| batch.put(key, value))
+    }
+
+    pub fn delete(&self, key: impl AsRef<[u8]>) -> Result<()> {
+        self.commit(|batch| batch.delete(key))
+    }
+
+    fn commit(&self, add: impl FnOnce(&mut WriteBatch)) -> Result<()> {
+        let mut pending = self.lock();
+        add(&mut pending.batch);
+        let group = pending.group.clone();
+        if pending.has_leader {
+            if pending.batch.size_in_bytes() >= self.max_batch_bytes {
+                self.full.notify_one();
+            }
+            drop(pending);
+            return group.wait();
+        }
+
+        pending.has_leader = true;
+        let deadline = Instant::now() + self.max_delay;
+        while pending.batch.size_in_bytes() < self.max_batch_bytes {
+            let timeout = match deadline.checked_duration_since(Instant::now()) {
+                Some(timeout) if !timeout.is_zero() => timeout,
+                _ => break,
+            };
+            pending = self
+                .full
+                .wait_timeout(pending, timeout)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+        let batch = mem::take(&mut pending.batch);
+        pending.group = Default::default();
+        pending.has_leader = false;
+        drop(pending);
+
+        let result = self.db.write(&self.options, &batch);
+        group.finish(result.clone());
+        result
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Pending> {
+        self.pending.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Group {
+    fn wait(&self) -> Result<()> {
+        let mut result = self.result.lock().unwrap_or_else(PoisonError::into_inner);
+        loop {
+            if let Some(result) = &*result {
+                return result.clone();
+            }
+            result = self
+                .done
+                .wait(result)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    fn finish(&self, result: Result<()>) {
+        *self.result.lock().unwrap_or_else(PoisonError::into_inner) = Some(result);
+        self.done.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::options::tests::DBPath;
+    use crate::{Options, WriteCoalescer, DB};
+
+    #[test]
+    fn test_write_coalescer() {
+        let mut options = Options::new();
+        options.set_create_if_missing(true);
+        let path = DBPath::new();
+        let db = DB::open(&options, path.as_ref()).unwrap();
+
+        let mut coalescer = WriteCoalescer::new(&db);
+        coalescer
+            .set_max_delay(Duration::from_millis(5))
+            .set_max_batch_bytes(1 << 10);
+        thread::scope(|s| {
+            for t in 0..8u32 {
+                let coalescer = &coalescer;
+                s.spawn(move || {
+                    for i in 0..50u32 {
+                        coalescer
+                            .put(format!("{}-{}", t, i), i.to_be_bytes())
+                            .unwrap();
+                    }
+                    coalescer.delete(format!("{}-0", t)).unwrap();
+                });
+            }
+        });
+
+        assert_eq!(db.iter(&Default::default()).count(), 8 * 49);
+        assert_eq!(db.get_default("3-7").unwrap().unwrap(), 7u32.to_be_bytes());
+        assert!(db.get_default("3-0").unwrap().is_none());
+    }
+}